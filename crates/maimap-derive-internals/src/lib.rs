@@ -0,0 +1,491 @@
+//! `#[derive(ToResponse)]` 的代码生成。
+//!
+//! 与 `maimap-derive` 分开，以便不经过 `proc_macro` 直接展开，在测试中比较展开结果。
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{
+    Attribute, Data, DeriveInput, ExprPath, Field, Fields, GenericArgument, Ident, LitStr,
+    PathArguments, Type, Variant, Visibility,
+};
+
+/// 生成 `#[derive(ToResponse)]` 的代码，错误指向出错的字段或属性。
+///
+/// 响应类型定义为以各字段响应类型为参数的泛型 `<类型名>ResponseOf`，`<类型名>Response` 是代入
+/// 实际类型后的别名。字段类型的约束只写在 `ToResponse` 与 `ResponseField` 的实现上，
+/// 字段类型未实现所需 trait 时只在该字段的类型上报告一条错误。
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let vis = &input.vis;
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "ToResponse不支持带泛型参数的类型",
+        ));
+    }
+    let response_name = format_ident!("{}Response", name);
+    let response_of = format_ident!("{}ResponseOf", name);
+    let response_doc = format!("`{}` 在接口响应中的结构", name);
+    let response_of_doc = format!(
+        "`{}` 的定义，类型参数依次为各字段在响应中的类型",
+        response_name
+    );
+
+    let (definition, fields, body) = match &input.data {
+        Data::Struct(data) => {
+            let fields = match &data.fields {
+                Fields::Named(fields) => &fields.named,
+                Fields::Unnamed(fields) => {
+                    return Err(syn::Error::new_spanned(
+                        fields,
+                        "ToResponse只支持命名字段的结构体",
+                    ));
+                }
+                Fields::Unit => {
+                    return Err(syn::Error::new_spanned(
+                        name,
+                        "ToResponse只支持命名字段的结构体",
+                    ));
+                }
+            };
+            let accessors = fields.iter().map(|field| {
+                let ident = &field.ident;
+                quote! { &self.#ident }
+            });
+            let fields = response_fields(fields.iter().zip(accessors))?;
+            let mut params = TypeParams::default();
+            let definitions: Vec<_> = fields
+                .iter()
+                .map(|field| field.definition(true, &mut params))
+                .collect();
+            let values: Vec<_> = fields.iter().map(ResponseFieldCode::value).collect();
+            let generics = params.generics();
+            (
+                quote! {
+                    #vis struct #response_of #generics {
+                        #(#definitions,)*
+                    }
+                },
+                fields,
+                quote! {
+                    #response_name {
+                        #(#values,)*
+                    }
+                },
+            )
+        }
+        Data::Enum(data) => {
+            let mut params = TypeParams::default();
+            let mut definitions = Vec::new();
+            let mut arms = Vec::new();
+            let mut fields = Vec::new();
+            for variant in &data.variants {
+                let (definition, arm, variant_fields) =
+                    response_variant(variant, &response_name, &mut params)?;
+                definitions.push(definition);
+                arms.push(arm);
+                fields.extend(variant_fields);
+            }
+            let generics = params.generics();
+            (
+                quote! {
+                    #vis enum #response_of #generics {
+                        #(#definitions,)*
+                    }
+                },
+                fields,
+                quote! {
+                    match self {
+                        #(#arms)*
+                    }
+                },
+            )
+        }
+        Data::Union(data) => {
+            return Err(syn::Error::new_spanned(
+                data.union_token,
+                "ToResponse只能用于结构体或枚举",
+            ));
+        }
+    };
+
+    let args: Vec<_> = fields.iter().filter_map(ResponseFieldCode::arg).collect();
+    let bounds: Vec<_> = fields.iter().filter_map(ResponseFieldCode::bound).collect();
+    let args = match args.is_empty() {
+        true => quote! {},
+        false => quote! { <#(#args),*> },
+    };
+    let where_clause = match bounds.is_empty() {
+        true => quote! {},
+        false => quote! { where #(#bounds,)* },
+    };
+
+    Ok(quote! {
+        #[doc = #response_of_doc]
+        #[derive(
+            Clone,
+            Debug,
+            PartialEq,
+            ::maimap_utils::serde::Serialize,
+            ::maimap_utils::serde::Deserialize,
+        )]
+        #[serde(crate = "::maimap_utils::serde")]
+        #definition
+
+        #[doc = #response_doc]
+        #vis type #response_name = #response_of #args;
+
+        impl ::maimap_utils::traits::ToResponse for #name #where_clause {
+            type Response = #response_name;
+
+            fn to_typed_response(&self) -> #response_name {
+                #body
+            }
+        }
+
+        impl ::maimap_utils::traits::ResponseField for #name #where_clause {
+            type Response = #response_name;
+
+            fn to_response_field(&self) -> #response_name {
+                ::maimap_utils::traits::ToResponse::to_typed_response(self)
+            }
+        }
+    })
+}
+
+/// 依次为字段分配响应类型的类型参数 `T0`、`T1`……
+#[derive(Default)]
+struct TypeParams(Vec<Ident>);
+
+impl TypeParams {
+    fn next(&mut self) -> Ident {
+        let param = format_ident!("T{}", self.0.len());
+        self.0.push(param.clone());
+        param
+    }
+
+    fn generics(&self) -> TokenStream2 {
+        let params = &self.0;
+        match params.is_empty() {
+            true => quote! {},
+            false => quote! { <#(#params),*> },
+        }
+    }
+}
+
+/// 字段或变体上 `#[response(...)]` 属性的选项
+#[derive(Default)]
+struct ResponseOptions {
+    rename: Option<String>,
+    skip_if: Option<ExprPath>,
+    with: Option<ExprPath>,
+    flatten: bool,
+}
+
+impl ResponseOptions {
+    fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut options = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("response")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    options.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("skip_if") {
+                    options.skip_if = Some(parse_fn_path(&meta)?);
+                } else if meta.path.is_ident("with") {
+                    options.with = Some(parse_fn_path(&meta)?);
+                } else if meta.path.is_ident("flatten") {
+                    options.flatten = true;
+                } else {
+                    return Err(
+                        meta.error("未知的 response 选项，可用的有 rename、skip_if、with、flatten")
+                    );
+                }
+                Ok(())
+            })?;
+        }
+        Ok(options)
+    }
+}
+
+/// 函数路径既可以直接书写，也可以像 serde 一样写在字符串中
+fn parse_fn_path(meta: &syn::meta::ParseNestedMeta) -> syn::Result<ExprPath> {
+    let value = meta.value()?;
+    let path = if value.peek(LitStr) {
+        value.parse::<LitStr>()?.parse()
+    } else {
+        value.parse()
+    };
+    path.map_err(|e| syn::Error::new(e.span(), "需要函数路径，如 path::to_fn 或 \"path::to_fn\""))
+}
+
+/// 响应类型中的一个字段：定义、取值与对字段类型的约束
+struct ResponseFieldCode<'a> {
+    field: &'a Field,
+    options: ResponseOptions,
+    access: TokenStream2,
+}
+
+impl ResponseFieldCode<'_> {
+    /// 字段的定义，需要类型参数时从 `params` 分配；`with_vis` 为假时用于枚举变体，不带可见性
+    fn definition(&self, with_vis: bool, params: &mut TypeParams) -> TokenStream2 {
+        let ident = &self.field.ident;
+        let docs = self
+            .field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("doc"));
+        let vis = match with_vis {
+            true => self.field.vis.clone(),
+            false => Visibility::Inherited,
+        };
+
+        let mut serde_attrs = Vec::new();
+        if let Some(rename) = &self.options.rename {
+            serde_attrs.push(quote! { #[serde(rename = #rename)] });
+        }
+        if self.options.flatten {
+            serde_attrs.push(quote! { #[serde(flatten)] });
+        }
+
+        let mut response_ty = match self.options.with {
+            Some(_) => quote! { ::maimap_utils::serde_json::Value },
+            None => {
+                let param = params.next();
+                quote! { #param }
+            }
+        };
+        if self.options.skip_if.is_some() {
+            response_ty = quote! { Option<#response_ty> };
+            serde_attrs.push(quote! { #[serde(default, skip_serializing_if = "Option::is_none")] });
+        }
+
+        quote! {
+            #(#docs)*
+            #(#serde_attrs)*
+            #vis #ident: #response_ty
+        }
+    }
+
+    /// 使用 `skip_if` 的 `Option` 字段转换其内层类型，响应中不再嵌套一层 `Option`
+    fn option_inner(&self) -> Option<&Type> {
+        if self.options.skip_if.is_none() || self.options.with.is_some() || self.options.flatten {
+            return None;
+        }
+        option_inner(&self.field.ty)
+    }
+
+    /// 需要转换的类型，使用 `with` 时没有
+    fn converted_ty(&self) -> Option<&Type> {
+        match self.options.with {
+            Some(_) => None,
+            None => Some(self.option_inner().unwrap_or(&self.field.ty)),
+        }
+    }
+
+    /// 转换所用的 trait，带有字段类型的位置
+    fn converter(&self) -> TokenStream2 {
+        let span = self.field.ty.span();
+        match self.options.flatten {
+            true => quote_spanned! {span=> ::maimap_utils::traits::DerivedFlatten },
+            false => quote_spanned! {span=> ::maimap_utils::traits::DerivedField },
+        }
+    }
+
+    /// 别名中代入该字段类型参数的实际类型
+    fn arg(&self) -> Option<TokenStream2> {
+        let ty = self.converted_ty()?;
+        let converter = self.converter();
+        Some(quote_spanned! {self.field.ty.span()=> <#ty as #converter>::Response })
+    }
+
+    /// 实现上对字段类型的约束，未满足时错误指向该字段的类型
+    fn bound(&self) -> Option<TokenStream2> {
+        let ty = self.converted_ty()?;
+        let converter = self.converter();
+        Some(quote_spanned! {self.field.ty.span()=> #ty: #converter })
+    }
+
+    /// 构造响应时字段的取值
+    fn value(&self) -> TokenStream2 {
+        let ident = &self.field.ident;
+        let access = &self.access;
+        let span = self.field.ty.span();
+        let unwrapped = self.option_inner().is_some();
+        let mut value = if let Some(with) = &self.options.with {
+            quote! { #with(#access) }
+        } else if self.options.flatten {
+            quote_spanned! {span=> ::maimap_utils::traits::DerivedFlatten::derived_flatten(#access) }
+        } else if unwrapped {
+            quote_spanned! {span=>
+                Option::map(Option::as_ref(#access), ::maimap_utils::traits::DerivedField::derived_field)
+            }
+        } else {
+            quote_spanned! {span=> ::maimap_utils::traits::DerivedField::derived_field(#access) }
+        };
+        if let Some(skip_if) = &self.options.skip_if {
+            if !unwrapped {
+                value = quote! { Some(#value) };
+            }
+            value = quote! {
+                if #skip_if(#access) { None } else { #value }
+            };
+        }
+        quote! { #ident: #value }
+    }
+}
+
+/// `Option<T>` 中的 `T`
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    match arguments.args.first()? {
+        GenericArgument::Type(inner) if arguments.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+/// 收集需要输出的命名字段，`access` 为取得各字段引用的表达式
+fn response_fields<'a>(
+    fields: impl Iterator<Item = (&'a Field, TokenStream2)>,
+) -> syn::Result<Vec<ResponseFieldCode<'a>>> {
+    let mut response_fields = Vec::new();
+    for (field, access) in fields {
+        // 检查是否有DoNotRespond属性
+        if field
+            .attrs
+            .iter()
+            .any(|attr| attr.path().is_ident("DoNotRespond"))
+        {
+            continue;
+        }
+        response_fields.push(ResponseFieldCode {
+            field,
+            options: ResponseOptions::from_attrs(&field.attrs)?,
+            access,
+        });
+    }
+    Ok(response_fields)
+}
+
+/// 生成响应枚举中的一个变体、其匹配分支，以及需要转换的字段
+fn response_variant<'a>(
+    variant: &'a Variant,
+    response_name: &Ident,
+    params: &mut TypeParams,
+) -> syn::Result<(TokenStream2, TokenStream2, Vec<ResponseFieldCode<'a>>)> {
+    let options = ResponseOptions::from_attrs(&variant.attrs)?;
+    if options.skip_if.is_some() || options.with.is_some() || options.flatten {
+        let attr = variant
+            .attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("response"));
+        return Err(syn::Error::new_spanned(
+            attr,
+            "枚举变体只支持 response(rename)",
+        ));
+    }
+    let ident = &variant.ident;
+    let key = options
+        .rename
+        .unwrap_or_else(|| to_snake_case(&ident.to_string()));
+    let docs = variant
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"));
+    let header = quote! {
+        #(#docs)*
+        #[serde(rename = #key)]
+    };
+
+    Ok(match &variant.fields {
+        Fields::Unit => (
+            quote! { #header #ident },
+            quote! { Self::#ident => #response_name::#ident, },
+            Vec::new(),
+        ),
+        Fields::Unnamed(fields) => {
+            let bindings: Vec<Ident> = (0..fields.unnamed.len())
+                .map(|index| format_ident!("field_{}", index))
+                .collect();
+            let fields: Vec<_> = fields
+                .unnamed
+                .iter()
+                .zip(&bindings)
+                .map(|(field, binding)| ResponseFieldCode {
+                    field,
+                    options: ResponseOptions::default(),
+                    access: quote! { #binding },
+                })
+                .collect();
+            let types: Vec<_> = fields.iter().map(|_| params.next()).collect();
+            let values = fields.iter().map(|field| {
+                let access = &field.access;
+                quote_spanned! {field.field.ty.span()=>
+                    ::maimap_utils::traits::DerivedField::derived_field(#access)
+                }
+            });
+            (
+                quote! {
+                    #header
+                    #ident(#(#types),*)
+                },
+                quote! {
+                    Self::#ident(#(#bindings),*) => #response_name::#ident(#(#values),*),
+                },
+                fields,
+            )
+        }
+        Fields::Named(fields) => {
+            // 绑定到带前缀的变量，避免与生成代码中的局部变量重名
+            let accessors = fields.named.iter().map(|field| {
+                let binding = format_ident!("field_{}", field.ident.as_ref().unwrap());
+                quote! { #binding }
+            });
+            let fields = response_fields(fields.named.iter().zip(accessors))?;
+            let names = fields.iter().map(|field| &field.field.ident);
+            let bindings = fields.iter().map(|field| &field.access);
+            let definitions: Vec<_> = fields
+                .iter()
+                .map(|field| field.definition(false, params))
+                .collect();
+            let values = fields.iter().map(ResponseFieldCode::value);
+            (
+                quote! {
+                    #header
+                    #ident { #(#definitions,)* }
+                },
+                quote! {
+                    Self::#ident { #(#names: #bindings,)* .. } => #response_name::#ident {
+                        #(#values,)*
+                    },
+                },
+                fields,
+            )
+        }
+    })
+}
+
+/// 将 `UpperCamelCase` 的变体名转换为 `snake_case`
+fn to_snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (index, ch) in name.char_indices() {
+        if ch.is_uppercase() {
+            if index > 0 {
+                snake.push('_');
+            }
+            snake.extend(ch.to_lowercase());
+        } else {
+            snake.push(ch);
+        }
+    }
+    snake
+}
@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod tests {
+    use maimap_derive_internals::expand;
+    use std::fs;
+    use std::path::Path;
+    use syn::{DeriveInput, parse_quote};
+
+    /// 将展开结果与 `tests/expand/<name>.expanded.rs` 比较。
+    ///
+    /// 只有设置环境变量 `EXPAND=overwrite` 时才写入新的展开结果，快照缺失时测试失败
+    fn assert_expansion(name: &str, input: DeriveInput) {
+        let tokens = expand(&input).unwrap();
+        let expanded = prettyplease::unparse(&syn::parse2(tokens).unwrap());
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/expand")
+            .join(format!("{}.expanded.rs", name));
+
+        if std::env::var("EXPAND").as_deref() == Ok("overwrite") {
+            fs::write(&path, &expanded).unwrap();
+            return;
+        }
+        let expected = fs::read_to_string(&path).unwrap_or_else(|e| {
+            panic!(
+                "无法读取快照 {}：{}，使用 EXPAND=overwrite 生成",
+                path.display(),
+                e
+            )
+        });
+        assert_eq!(
+            expanded,
+            expected,
+            "{} 的展开结果与快照不一致，确认无误后使用 EXPAND=overwrite 更新",
+            path.display()
+        );
+    }
+
+    #[test]
+    fn test_expand_do_not_respond() {
+        assert_expansion(
+            "do_not_respond",
+            parse_quote! {
+                pub struct Arcade {
+                    /// 机厅名
+                    pub arcade_name: String,
+                    #[DoNotRespond]
+                    pub arcade_pos: Option<Point>,
+                    #[DoNotRespond]
+                    #[serde(default)]
+                    pub arcade_missing_count: i32,
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn test_expand_special_types() {
+        assert_expansion(
+            "special_types",
+            parse_quote! {
+                pub struct Record {
+                    pub id: ObjectId,
+                    pub created_at: DateTime,
+                    pub rating: Decimal128,
+                    pub updated_at: Option<DateTime>,
+                    pub user_ids: Vec<ObjectId>,
+                    pub prices: Vec<Option<Decimal128>>,
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn test_expand_options() {
+        assert_expansion(
+            "options",
+            parse_quote! {
+                struct Shop {
+                    #[response(rename = "shop_name")]
+                    name: String,
+                    #[response(skip_if = "Option::is_none")]
+                    phone: Option<String>,
+                    #[response(skip_if = is_zero)]
+                    count: i32,
+                    #[response(with = yuan)]
+                    cost: f64,
+                    #[response(flatten)]
+                    location: Location,
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn test_expand_enum() {
+        assert_expansion(
+            "enum",
+            parse_quote! {
+                enum Change {
+                    Opened,
+                    #[response(rename = "closed")]
+                    MarkedClosed,
+                    Moved(Decimal128, Decimal128),
+                    Merged {
+                        into: ObjectId,
+                        #[DoNotRespond]
+                        reason: String,
+                    },
+                }
+            },
+        );
+    }
+}
@@ -0,0 +1,39 @@
+///`ArcadeResponse` 的定义，类型参数依次为各字段在响应中的类型
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    ::maimap_utils::serde::Serialize,
+    ::maimap_utils::serde::Deserialize,
+)]
+#[serde(crate = "::maimap_utils::serde")]
+pub struct ArcadeResponseOf<T0> {
+    /// 机厅名
+    pub arcade_name: T0,
+}
+///`Arcade` 在接口响应中的结构
+pub type ArcadeResponse = ArcadeResponseOf<
+    <String as ::maimap_utils::traits::DerivedField>::Response,
+>;
+impl ::maimap_utils::traits::ToResponse for Arcade
+where
+    String: ::maimap_utils::traits::DerivedField,
+{
+    type Response = ArcadeResponse;
+    fn to_typed_response(&self) -> ArcadeResponse {
+        ArcadeResponse {
+            arcade_name: ::maimap_utils::traits::DerivedField::derived_field(
+                &self.arcade_name,
+            ),
+        }
+    }
+}
+impl ::maimap_utils::traits::ResponseField for Arcade
+where
+    String: ::maimap_utils::traits::DerivedField,
+{
+    type Response = ArcadeResponse;
+    fn to_response_field(&self) -> ArcadeResponse {
+        ::maimap_utils::traits::ToResponse::to_typed_response(self)
+    }
+}
@@ -0,0 +1,61 @@
+///`ChangeResponse` 的定义，类型参数依次为各字段在响应中的类型
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    ::maimap_utils::serde::Serialize,
+    ::maimap_utils::serde::Deserialize,
+)]
+#[serde(crate = "::maimap_utils::serde")]
+enum ChangeResponseOf<T0, T1, T2> {
+    #[serde(rename = "opened")]
+    Opened,
+    #[serde(rename = "closed")]
+    MarkedClosed,
+    #[serde(rename = "moved")]
+    Moved(T0, T1),
+    #[serde(rename = "merged")]
+    Merged { into: T2 },
+}
+///`Change` 在接口响应中的结构
+type ChangeResponse = ChangeResponseOf<
+    <Decimal128 as ::maimap_utils::traits::DerivedField>::Response,
+    <Decimal128 as ::maimap_utils::traits::DerivedField>::Response,
+    <ObjectId as ::maimap_utils::traits::DerivedField>::Response,
+>;
+impl ::maimap_utils::traits::ToResponse for Change
+where
+    Decimal128: ::maimap_utils::traits::DerivedField,
+    Decimal128: ::maimap_utils::traits::DerivedField,
+    ObjectId: ::maimap_utils::traits::DerivedField,
+{
+    type Response = ChangeResponse;
+    fn to_typed_response(&self) -> ChangeResponse {
+        match self {
+            Self::Opened => ChangeResponse::Opened,
+            Self::MarkedClosed => ChangeResponse::MarkedClosed,
+            Self::Moved(field_0, field_1) => {
+                ChangeResponse::Moved(
+                    ::maimap_utils::traits::DerivedField::derived_field(field_0),
+                    ::maimap_utils::traits::DerivedField::derived_field(field_1),
+                )
+            }
+            Self::Merged { into: field_into, .. } => {
+                ChangeResponse::Merged {
+                    into: ::maimap_utils::traits::DerivedField::derived_field(field_into),
+                }
+            }
+        }
+    }
+}
+impl ::maimap_utils::traits::ResponseField for Change
+where
+    Decimal128: ::maimap_utils::traits::DerivedField,
+    Decimal128: ::maimap_utils::traits::DerivedField,
+    ObjectId: ::maimap_utils::traits::DerivedField,
+{
+    type Response = ChangeResponse;
+    fn to_response_field(&self) -> ChangeResponse {
+        ::maimap_utils::traits::ToResponse::to_typed_response(self)
+    }
+}
@@ -0,0 +1,70 @@
+///`ShopResponse` 的定义，类型参数依次为各字段在响应中的类型
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    ::maimap_utils::serde::Serialize,
+    ::maimap_utils::serde::Deserialize,
+)]
+#[serde(crate = "::maimap_utils::serde")]
+struct ShopResponseOf<T0, T1, T2, T3> {
+    #[serde(rename = "shop_name")]
+    name: T0,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    phone: Option<T1>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    count: Option<T2>,
+    cost: ::maimap_utils::serde_json::Value,
+    #[serde(flatten)]
+    location: T3,
+}
+///`Shop` 在接口响应中的结构
+type ShopResponse = ShopResponseOf<
+    <String as ::maimap_utils::traits::DerivedField>::Response,
+    <String as ::maimap_utils::traits::DerivedField>::Response,
+    <i32 as ::maimap_utils::traits::DerivedField>::Response,
+    <Location as ::maimap_utils::traits::DerivedFlatten>::Response,
+>;
+impl ::maimap_utils::traits::ToResponse for Shop
+where
+    String: ::maimap_utils::traits::DerivedField,
+    String: ::maimap_utils::traits::DerivedField,
+    i32: ::maimap_utils::traits::DerivedField,
+    Location: ::maimap_utils::traits::DerivedFlatten,
+{
+    type Response = ShopResponse;
+    fn to_typed_response(&self) -> ShopResponse {
+        ShopResponse {
+            name: ::maimap_utils::traits::DerivedField::derived_field(&self.name),
+            phone: if Option::is_none(&self.phone) {
+                None
+            } else {
+                Option::map(
+                    Option::as_ref(&self.phone),
+                    ::maimap_utils::traits::DerivedField::derived_field,
+                )
+            },
+            count: if is_zero(&self.count) {
+                None
+            } else {
+                Some(::maimap_utils::traits::DerivedField::derived_field(&self.count))
+            },
+            cost: yuan(&self.cost),
+            location: ::maimap_utils::traits::DerivedFlatten::derived_flatten(
+                &self.location,
+            ),
+        }
+    }
+}
+impl ::maimap_utils::traits::ResponseField for Shop
+where
+    String: ::maimap_utils::traits::DerivedField,
+    String: ::maimap_utils::traits::DerivedField,
+    i32: ::maimap_utils::traits::DerivedField,
+    Location: ::maimap_utils::traits::DerivedFlatten,
+{
+    type Response = ShopResponse;
+    fn to_response_field(&self) -> ShopResponse {
+        ::maimap_utils::traits::ToResponse::to_typed_response(self)
+    }
+}
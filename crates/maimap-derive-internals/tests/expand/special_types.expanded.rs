@@ -0,0 +1,67 @@
+///`RecordResponse` 的定义，类型参数依次为各字段在响应中的类型
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    ::maimap_utils::serde::Serialize,
+    ::maimap_utils::serde::Deserialize,
+)]
+#[serde(crate = "::maimap_utils::serde")]
+pub struct RecordResponseOf<T0, T1, T2, T3, T4, T5> {
+    pub id: T0,
+    pub created_at: T1,
+    pub rating: T2,
+    pub updated_at: T3,
+    pub user_ids: T4,
+    pub prices: T5,
+}
+///`Record` 在接口响应中的结构
+pub type RecordResponse = RecordResponseOf<
+    <ObjectId as ::maimap_utils::traits::DerivedField>::Response,
+    <DateTime as ::maimap_utils::traits::DerivedField>::Response,
+    <Decimal128 as ::maimap_utils::traits::DerivedField>::Response,
+    <Option<DateTime> as ::maimap_utils::traits::DerivedField>::Response,
+    <Vec<ObjectId> as ::maimap_utils::traits::DerivedField>::Response,
+    <Vec<Option<Decimal128>> as ::maimap_utils::traits::DerivedField>::Response,
+>;
+impl ::maimap_utils::traits::ToResponse for Record
+where
+    ObjectId: ::maimap_utils::traits::DerivedField,
+    DateTime: ::maimap_utils::traits::DerivedField,
+    Decimal128: ::maimap_utils::traits::DerivedField,
+    Option<DateTime>: ::maimap_utils::traits::DerivedField,
+    Vec<ObjectId>: ::maimap_utils::traits::DerivedField,
+    Vec<Option<Decimal128>>: ::maimap_utils::traits::DerivedField,
+{
+    type Response = RecordResponse;
+    fn to_typed_response(&self) -> RecordResponse {
+        RecordResponse {
+            id: ::maimap_utils::traits::DerivedField::derived_field(&self.id),
+            created_at: ::maimap_utils::traits::DerivedField::derived_field(
+                &self.created_at,
+            ),
+            rating: ::maimap_utils::traits::DerivedField::derived_field(&self.rating),
+            updated_at: ::maimap_utils::traits::DerivedField::derived_field(
+                &self.updated_at,
+            ),
+            user_ids: ::maimap_utils::traits::DerivedField::derived_field(
+                &self.user_ids,
+            ),
+            prices: ::maimap_utils::traits::DerivedField::derived_field(&self.prices),
+        }
+    }
+}
+impl ::maimap_utils::traits::ResponseField for Record
+where
+    ObjectId: ::maimap_utils::traits::DerivedField,
+    DateTime: ::maimap_utils::traits::DerivedField,
+    Decimal128: ::maimap_utils::traits::DerivedField,
+    Option<DateTime>: ::maimap_utils::traits::DerivedField,
+    Vec<ObjectId>: ::maimap_utils::traits::DerivedField,
+    Vec<Option<Decimal128>>: ::maimap_utils::traits::DerivedField,
+{
+    type Response = RecordResponse;
+    fn to_response_field(&self) -> RecordResponse {
+        ::maimap_utils::traits::ToResponse::to_typed_response(self)
+    }
+}
@@ -0,0 +1,9 @@
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_derive_ui() {
+        let cases = trybuild::TestCases::new();
+        cases.pass("tests/ui/pass/*.rs");
+        cases.compile_fail("tests/ui/fail/*.rs");
+    }
+}
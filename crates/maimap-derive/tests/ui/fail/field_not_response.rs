@@ -0,0 +1,13 @@
+use maimap_derive::ToResponse;
+
+struct Owner {
+    name: String,
+}
+
+#[derive(ToResponse)]
+struct Shop {
+    name: String,
+    owner: Owner,
+}
+
+fn main() {}
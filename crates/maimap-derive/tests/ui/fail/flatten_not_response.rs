@@ -0,0 +1,14 @@
+use maimap_derive::ToResponse;
+
+struct Owner {
+    name: String,
+}
+
+#[derive(ToResponse)]
+struct Shop {
+    name: String,
+    #[response(flatten)]
+    owner: Owner,
+}
+
+fn main() {}
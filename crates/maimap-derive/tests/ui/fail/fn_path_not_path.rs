@@ -0,0 +1,9 @@
+use maimap_derive::ToResponse;
+
+#[derive(ToResponse)]
+struct Shop {
+    #[response(skip_if = 123)]
+    phone: Option<String>,
+}
+
+fn main() {}
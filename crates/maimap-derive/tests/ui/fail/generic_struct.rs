@@ -0,0 +1,8 @@
+use maimap_derive::ToResponse;
+
+#[derive(ToResponse)]
+struct Page<T> {
+    items: Vec<T>,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use maimap_derive::ToResponse;
+
+#[derive(ToResponse)]
+struct Shop {
+    #[response(rename = shop_name)]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use maimap_derive::ToResponse;
+
+#[derive(ToResponse)]
+struct Point(f64, f64);
+
+fn main() {}
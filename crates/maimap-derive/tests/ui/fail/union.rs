@@ -0,0 +1,9 @@
+use maimap_derive::ToResponse;
+
+#[derive(ToResponse)]
+union Number {
+    int: i32,
+    float: f32,
+}
+
+fn main() {}
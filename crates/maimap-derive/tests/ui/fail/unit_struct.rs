@@ -0,0 +1,6 @@
+use maimap_derive::ToResponse;
+
+#[derive(ToResponse)]
+struct Empty;
+
+fn main() {}
@@ -0,0 +1,10 @@
+use maimap_derive::ToResponse;
+
+#[derive(ToResponse)]
+struct Shop {
+    name: String,
+    #[response(skip)]
+    phone: String,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use maimap_derive::ToResponse;
+
+#[derive(ToResponse)]
+enum Status {
+    Open,
+    #[response(flatten)]
+    Closed,
+}
+
+fn main() {}
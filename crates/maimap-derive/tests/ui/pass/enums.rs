@@ -0,0 +1,48 @@
+use maimap_derive::ToResponse;
+use maimap_utils::traits::ToResponse;
+use mongodb::bson::oid::ObjectId;
+use serde_json::json;
+
+#[derive(ToResponse)]
+enum Change {
+    Opened,
+    #[response(rename = "closed")]
+    MarkedClosed,
+    Renamed(String),
+    Moved(f64, f64),
+    Merged {
+        into: ObjectId,
+        #[DoNotRespond]
+        #[allow(dead_code)]
+        reason: String,
+    },
+}
+
+fn main() {
+    let into = ObjectId::parse_str("67fcfa2b8c5b2f0001a1b2c3").unwrap();
+    let changes = vec![
+        Change::Opened,
+        Change::MarkedClosed,
+        Change::Renamed("大玩家".to_string()),
+        Change::Moved(31.2, 121.4),
+        Change::Merged {
+            into,
+            reason: "重复".to_string(),
+        },
+    ];
+
+    assert_eq!(
+        changes.to_response(),
+        json!([
+            "opened",
+            "closed",
+            { "renamed": "大玩家" },
+            { "moved": [31.2, 121.4] },
+            { "merged": { "into": "67fcfa2b8c5b2f0001a1b2c3" } },
+        ])
+    );
+    assert_eq!(
+        Change::MarkedClosed.to_typed_response(),
+        ChangeResponse::MarkedClosed
+    );
+}
@@ -0,0 +1,56 @@
+use maimap_derive::ToResponse;
+use maimap_utils::traits::ToResponse;
+use serde_json::{Value, json};
+
+fn yuan(cost: &f64) -> Value {
+    json!(format!("{:.1}元", cost))
+}
+
+fn is_zero(count: &i32) -> bool {
+    *count == 0
+}
+
+#[derive(ToResponse)]
+struct Location {
+    province: String,
+}
+
+#[derive(ToResponse)]
+struct Shop {
+    #[response(rename = "shop_name")]
+    name: String,
+    #[response(skip_if = "Option::is_none")]
+    phone: Option<String>,
+    #[response(skip_if = "is_zero")]
+    count: i32,
+    #[response(with = yuan)]
+    cost: f64,
+    #[response(flatten)]
+    location: Location,
+}
+
+fn main() {
+    let shop = Shop {
+        name: "大玩家".to_string(),
+        phone: None,
+        count: 0,
+        cost: 2.0,
+        location: Location {
+            province: "上海".to_string(),
+        },
+    };
+    let value = shop.to_response();
+    assert_eq!(
+        value,
+        json!({ "shop_name": "大玩家", "cost": "2.0元", "province": "上海" })
+    );
+
+    let parsed: ShopResponse = serde_json::from_value(value).unwrap();
+    assert_eq!(parsed, shop.to_typed_response());
+    // 字段本身是 Option 时不会再嵌套一层，其他字段包装为 Option
+    let phone: Option<String> = parsed.phone;
+    let count: Option<i32> = parsed.count;
+    assert_eq!(phone, None);
+    assert_eq!(count, None);
+    assert_eq!(parsed.location.province, "上海");
+}
@@ -0,0 +1,62 @@
+use maimap_derive::ToResponse;
+use maimap_utils::traits::ToResponse;
+use mongodb::bson::oid::ObjectId;
+use mongodb::bson::{DateTime, Decimal128};
+use serde_json::json;
+
+#[derive(ToResponse)]
+struct Record {
+    id: ObjectId,
+    created_at: DateTime,
+    rating: Decimal128,
+    user_ids: Vec<ObjectId>,
+    updated_at: Option<DateTime>,
+    deleted_at: Option<DateTime>,
+    price: Option<Decimal128>,
+    name: String,
+    count: i32,
+    #[DoNotRespond]
+    #[allow(dead_code)]
+    secret: String,
+}
+
+fn main() {
+    let id = ObjectId::parse_str("67fcfa2b8c5b2f0001a1b2c3").unwrap();
+    let record = Record {
+        id,
+        created_at: DateTime::from_millis(1744632360000),
+        rating: "4.5".parse().unwrap(),
+        user_ids: vec![id],
+        updated_at: Some(DateTime::from_millis(1744632360000)),
+        deleted_at: None,
+        price: Some("2".parse().unwrap()),
+        name: "大玩家".to_string(),
+        count: 4,
+        secret: "不输出".to_string(),
+    };
+
+    assert_eq!(
+        record.to_response(),
+        json!({
+            "id": "67fcfa2b8c5b2f0001a1b2c3",
+            "created_at": "2025-04-14T12:06:00.000Z",
+            "rating": 4.5,
+            "user_ids": ["67fcfa2b8c5b2f0001a1b2c3"],
+            "updated_at": "2025-04-14T12:06:00.000Z",
+            "deleted_at": null,
+            "price": 2.0,
+            "name": "大玩家",
+            "count": 4,
+        })
+    );
+
+    // 生成的响应类型中，特殊类型被转换为 JSON 中对应的基本类型
+    let response: RecordResponse = record.to_typed_response();
+    let _: String = response.id;
+    let _: String = response.created_at;
+    let _: f64 = response.rating;
+    let _: Vec<String> = response.user_ids;
+    let _: Option<String> = response.updated_at;
+    let _: Option<f64> = response.price;
+    let _: i32 = response.count;
+}
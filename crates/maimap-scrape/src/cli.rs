@@ -0,0 +1,139 @@
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+use maimap_scrape::scrape::ScrapeOptions;
+use maimap_scrape::store_source::StoreSourceKind;
+use maimap_utils::env::{scrape_html_archive_path, scrape_store_source};
+use std::path::PathBuf;
+use tracing::Level;
+
+/// MaiMap 爬虫与数据维护工具。
+///
+/// 不带子命令运行时依次执行数据库迁移、爬取与备份，供定时任务使用。
+#[derive(Parser)]
+#[command(version, about)]
+pub(crate) struct Cli {
+    /// 环境变量文件路径，优先于自动查找到的 .env
+    #[arg(long, global = true, value_name = "PATH")]
+    pub(crate) config: Option<PathBuf>,
+
+    /// 只输出将要执行的操作，不写入数据库、不上传文件。
+    /// 爬取时不解析新地址，因此不会识别只能按坐标判断的改名
+    #[arg(long, global = true)]
+    pub(crate) dry_run: bool,
+
+    /// 输出更详细的日志，可重复使用（-vv）
+    #[arg(short, long, global = true, action = ArgAction::Count, conflicts_with = "quiet")]
+    pub(crate) verbose: u8,
+
+    /// 只输出警告与错误
+    #[arg(short, long, global = true)]
+    pub(crate) quiet: bool,
+
+    #[command(subcommand)]
+    pub(crate) command: Option<Command>,
+}
+
+impl Cli {
+    pub(crate) fn log_level(&self) -> Level {
+        if self.quiet {
+            return Level::WARN;
+        }
+        match self.verbose {
+            0 => Level::INFO,
+            1 => Level::DEBUG,
+            _ => Level::TRACE,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub(crate) enum Command {
+    /// 爬取华立官网机厅并同步到数据库
+    Scrape(SourceArgs),
+    /// 查看、执行或回滚数据库迁移
+    Migrate {
+        #[command(subcommand)]
+        action: MigrateAction,
+    },
+    /// 创建缺失的数据库索引并报告不一致之处，--dry-run 时只报告
+    Indexes,
+    /// 备份数据库并保存到备份存储，之后清理过期的备份
+    Backup,
+    /// 按 BACKUP_KEEP_* 保留策略清理过期的备份，--dry-run 时只列出
+    Prune,
+    /// 从备份存储下载备份并恢复数据库，恢复后校验各集合的文档数
+    Restore {
+        /// 备份存储中的文件名或本地的 .gz 备份文件，省略时使用最新的备份
+        archive: Option<String>,
+        /// 只列出备份存储中的备份文件
+        #[arg(long, conflicts_with_all = ["archive", "target_db", "drop"])]
+        list: bool,
+        /// 恢复到此数据库，默认 maimap，可用于恢复到预发布环境
+        #[arg(long, value_name = "DB")]
+        target_db: Option<String>,
+        /// 恢复前删除已有集合
+        #[arg(long)]
+        drop: bool,
+    },
+    /// 按清单校验备份文件的大小、SHA-256 与各集合文档数
+    Verify {
+        /// 备份存储中的文件名或本地的备份文件，省略时校验存储中最新的备份
+        archive: Option<String>,
+    },
+    /// 导出数据库与网站的机厅名称用于比对
+    ExportNames(SourceArgs),
+    /// 调用腾讯地图解析地址
+    Geocode { address: String },
+    /// 常驻运行，启动时执行数据库迁移，之后按 SCHEDULE_* 中的 cron 表达式定期爬取与备份
+    Daemon(SourceArgs),
+}
+
+#[derive(Args, Default)]
+pub(crate) struct SourceArgs {
+    /// 机厅列表的数据来源，默认读取 SCRAPE_STORE_SOURCE
+    #[arg(long, value_enum)]
+    pub(crate) source: Option<StoreSourceKind>,
+
+    /// 从保存的页面快照（.html 或 .json）读取机厅列表，而不是访问华立官网
+    #[arg(long, value_name = "FILE")]
+    pub(crate) from_html: Option<PathBuf>,
+
+    /// 将抓取到的页面按时间戳保存到此目录，默认读取 SCRAPE_HTML_ARCHIVE_PATH
+    #[arg(long, value_name = "DIR")]
+    pub(crate) archive_html: Option<PathBuf>,
+}
+
+impl SourceArgs {
+    pub(crate) fn into_options(self, dry_run: bool) -> ScrapeOptions {
+        let archive_dir = self.archive_html.or_else(|| {
+            let path = scrape_html_archive_path();
+            (!path.is_empty()).then(|| PathBuf::from(path))
+        });
+        let source = self.source.unwrap_or_else(|| {
+            StoreSourceKind::from_str(&scrape_store_source(), true).unwrap_or_default()
+        });
+        ScrapeOptions {
+            dry_run,
+            source,
+            from_html: self.from_html,
+            archive_dir,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub(crate) enum MigrateAction {
+    /// 列出全部迁移及其执行情况
+    Status,
+    /// 执行未执行过的迁移
+    Up {
+        /// 只执行版本号不超过此值的迁移
+        #[arg(long, value_name = "VERSION")]
+        to: Option<i64>,
+    },
+    /// 回滚最近执行的迁移
+    Down {
+        /// 回滚所有版本号大于此值的迁移
+        #[arg(long, value_name = "VERSION")]
+        to: Option<i64>,
+    },
+}
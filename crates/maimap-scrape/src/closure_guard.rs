@@ -0,0 +1,85 @@
+use maimap_utils::env::{
+    scrape_allow_mass_closure, scrape_max_closure_count, scrape_max_closure_ratio,
+};
+use maimap_utils::errors::{AppError, Result};
+use maimap_utils::types::{Arcade, ScrapeRun};
+use tracing::warn;
+
+/// 防止页面加载不完整时把大量存活机厅误标记为关闭。
+pub struct ClosureGuard {
+    max_ratio: f64,
+    max_count: usize,
+    allow_override: bool,
+}
+
+impl ClosureGuard {
+    /// 创建关闭阈值检查。
+    ///
+    /// 单次最多关闭 `max_count` 个机厅，且不超过存活机厅的 `max_ratio`（0 到 1 之间）；
+    /// `allow_override` 为真时超过阈值也继续执行。
+    pub fn new(max_ratio: f64, max_count: usize, allow_override: bool) -> Self {
+        Self {
+            max_ratio,
+            max_count,
+            allow_override,
+        }
+    }
+
+    /// 从 `SCRAPE_MAX_CLOSURE_RATIO`、`SCRAPE_MAX_CLOSURE_COUNT` 和
+    /// `SCRAPE_ALLOW_MASS_CLOSURE` 读取阈值。
+    pub fn from_env() -> Self {
+        Self::new(
+            scrape_max_closure_ratio(),
+            scrape_max_closure_count(),
+            scrape_allow_mass_closure(),
+        )
+    }
+
+    /// 待关闭的 `closing` 个机厅是否超过阈值，`alive` 为当前存活的机厅数。
+    ///
+    /// 没有存活机厅时只按数量上限判断。
+    pub fn exceeds(&self, closing: usize, alive: usize) -> bool {
+        if closing > self.max_count {
+            return true;
+        }
+        alive > 0 && closing as f64 / alive as f64 > self.max_ratio
+    }
+
+    /// 是否可以继续关闭：未超过阈值，或已显式允许大批量关闭。
+    pub fn allows(&self, closing: usize, alive: usize) -> bool {
+        !self.exceeds(closing, alive) || self.allow_override
+    }
+
+    /// 检查本次待关闭的机厅数量。
+    ///
+    /// 超过阈值时将候选机厅记入日志和 `run.closure_candidate_ids`，
+    /// 未显式允许大批量关闭时返回 [`AppError::Scrape`] 中止爬取。
+    pub fn check(&self, closing: &[&Arcade], alive: usize, run: &mut ScrapeRun) -> Result<()> {
+        if !self.exceeds(closing.len(), alive) {
+            return Ok(());
+        }
+
+        for arcade in closing {
+            warn!(
+                "待关闭机厅：ID {}，名称 {}，地址：{}",
+                arcade.arcade_id, arcade.arcade_name, arcade.arcade_address
+            );
+        }
+        run.closure_candidate_ids = closing.iter().map(|arcade| arcade.arcade_id).collect();
+
+        let message = format!(
+            "本次将关闭 {} 个机厅（存活 {} 个），超过阈值（最多 {} 个或 {:.1}%）",
+            closing.len(),
+            alive,
+            self.max_count,
+            self.max_ratio * 100.0
+        );
+
+        if self.allows(closing.len(), alive) {
+            warn!("{}，已设置 SCRAPE_ALLOW_MASS_CLOSURE，继续执行", message);
+            Ok(())
+        } else {
+            Err(AppError::Scrape(message).into())
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::store_list::WebArcade;
 use maimap_utils::errors::{Context, Result};
 use maimap_utils::types::Arcade;
 use std::collections::{HashMap, HashSet};
@@ -8,7 +9,7 @@ use tracing::info;
 /// 将数据库和网站的机厅名称分别导出到文件，用于调试比对。
 pub(crate) async fn export_arcade_names_to_files(
     existing_arcades: &HashMap<String, Arcade>,
-    web_arcades: &[(String, String)],
+    web_arcades: &[WebArcade],
 ) -> Result<()> {
     info!("正在导出机厅名称用于比对...");
 
@@ -28,7 +29,10 @@ pub(crate) async fn export_arcade_names_to_files(
     );
 
     // 提取网站上的机厅名称并写入文件
-    let web_names: HashSet<_> = web_arcades.iter().map(|(name, _)| name.clone()).collect();
+    let web_names: HashSet<_> = web_arcades
+        .iter()
+        .map(|arcade| arcade.name.clone())
+        .collect();
     let mut file_web = File::create("web_arcades.txt")
         .await
         .context("创建 web_arcades.txt 失败")?;
@@ -1,10 +1,23 @@
-use maimap_utils::env::qmap_key;
-use maimap_utils::errors::AppError;
+use maimap_utils::env::{geocoder_concurrency, geocoder_qps, qmap_key};
+use maimap_utils::errors::{AppError, Result};
 use maimap_utils::types::Point;
 use serde::Deserialize;
 use std::fmt::{Display, Formatter};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::time::Duration;
-use tracing::info;
+use tokio::sync::Semaphore;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+const GEOCODER_URL: &str = "https://apis.map.qq.com/ws/geocoder/v1/";
+
+/// 此key每秒请求量已达到上限
+const STATUS_QPS_LIMIT: i32 = 120;
+/// 此key每日调用量已达到上限
+const STATUS_DAILY_LIMIT: i32 = 121;
+/// 地址无法精确解析，需添加 policy=1 参数
+const STATUS_NEEDS_POLICY: i32 = 348;
 
 #[derive(Deserialize)]
 pub(crate) struct GeocoderResponse {
@@ -19,13 +32,13 @@ pub(crate) struct GeocoderResult {
 }
 
 #[derive(Deserialize, Clone)]
-pub(crate) struct GeoLocation {
+pub struct GeoLocation {
     pub lat: f64,
     pub lng: f64,
 }
 
 impl GeoLocation {
-    pub(crate) fn to_point(&self) -> Point {
+    pub fn to_point(&self) -> Point {
         Point::new(self.lng, self.lat)
     }
 }
@@ -40,54 +53,248 @@ impl Display for GeoLocation {
         write!(f, "{},{}", self.lat, self.lng)
     }
 }
-pub(crate) async fn get_geo_location(address: &str) -> maimap_utils::errors::Result<GeoLocation> {
-    let client = reqwest::Client::new();
-    let max_retries = 3;
-    let base_delay = Duration::from_secs(2);
-    let mut use_policy = false;
-
-    for attempt in 0..=max_retries {
-        let key = qmap_key();
-        let mut params = vec![("address", address), ("key", &key)];
-
-        // 如果需要添加policy参数
-        if use_policy {
-            params.push(("policy", "1"));
+/// 令牌桶限流器，令牌按固定速率补充，最多积攒 `capacity` 个
+pub struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// 每秒补充 `qps` 个令牌，初始时令牌已满
+    pub fn new(qps: f64) -> Self {
+        let qps = qps.max(0.1);
+        let capacity = qps.max(1.0);
+        Self {
+            rate: qps,
+            capacity,
+            tokens: capacity,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// 尝试取走一个令牌，令牌不足时返回需要等待的时间
+    pub fn try_acquire(&mut self, now: Instant) -> Option<Duration> {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64((1.0 - self.tokens) / self.rate))
+        }
+    }
+}
+
+/// 被限流后所有请求共享的退避状态
+#[derive(Default)]
+struct Backoff {
+    until: Option<Instant>,
+    consecutive: u32,
+}
+
+/// 腾讯地图地址解析接口的客户端。
+///
+/// 同一实例可在多个任务间共享：并发请求数受信号量限制，请求速率受令牌桶限制，
+/// 收到限流状态码后所有请求一同按指数退避等待。
+pub struct Geocoder {
+    client: reqwest::Client,
+    url: String,
+    key: String,
+    concurrency: usize,
+    permits: Semaphore,
+    bucket: Mutex<TokenBucket>,
+    backoff: Mutex<Backoff>,
+    quota_exhausted: AtomicBool,
+    requests: AtomicU32,
+}
+
+impl Geocoder {
+    const MAX_RETRIES: i32 = 3;
+    const BASE_DELAY: Duration = Duration::from_secs(2);
+    const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+    pub fn new(
+        url: impl Into<String>,
+        key: impl Into<String>,
+        qps: f64,
+        concurrency: usize,
+    ) -> Self {
+        let concurrency = concurrency.max(1);
+        Self {
+            client: reqwest::Client::new(),
+            url: url.into(),
+            key: key.into(),
+            concurrency,
+            permits: Semaphore::new(concurrency),
+            bucket: Mutex::new(TokenBucket::new(qps)),
+            backoff: Mutex::new(Backoff::default()),
+            quota_exhausted: AtomicBool::new(false),
+            requests: AtomicU32::new(0),
+        }
+    }
+
+    /// 使用 QMAP_KEY、GEOCODER_QPS 与 GEOCODER_CONCURRENCY 创建
+    pub fn from_env() -> Self {
+        Self::new(
+            GEOCODER_URL,
+            qmap_key(),
+            geocoder_qps(),
+            geocoder_concurrency(),
+        )
+    }
+
+    /// 允许同时进行的请求数
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// 已向接口发出的请求数，包括重试
+    pub fn request_count(&self) -> u32 {
+        self.requests.load(Ordering::Relaxed)
+    }
+
+    pub async fn locate(&self, address: &str) -> Result<GeoLocation> {
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .map_err(|e| AppError::Geocoder(e.to_string()))?;
+        let mut use_policy = false;
+        let mut attempt = 0;
+
+        loop {
+            if self.quota_exhausted.load(Ordering::Relaxed) {
+                return Err(AppError::Geocoder("今日调用量已达到上限".to_string()).into());
+            }
+            self.wait_for_turn().await;
+
+            let mut params = vec![("address", address), ("key", &self.key)];
+            // 如果需要添加policy参数
+            if use_policy {
+                params.push(("policy", "1"));
+            }
+
+            // 网络错误、HTTP 错误状态与无法解析的响应同样按次数重试
+            let response = match self.request(&params).await {
+                Ok(response) => response,
+                Err(e) if attempt < Self::MAX_RETRIES => {
+                    let delay = Self::retry_delay(attempt);
+                    warn!(
+                        "请求地址解析接口失败：{}，将在 {:?} 后重试 ({}/{})",
+                        e,
+                        delay,
+                        attempt + 1,
+                        Self::MAX_RETRIES
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            match response.status {
+                0 => {
+                    self.reset_backoff();
+                    if let Some(result) = response.result {
+                        return Ok(result.location);
+                    }
+                    return Err(AppError::Geocoder(response.message).into());
+                }
+                STATUS_NEEDS_POLICY if !use_policy => {
+                    use_policy = true;
+                    info!("收到状态码348，添加policy=1参数并立即重试");
+                    continue;
+                }
+                STATUS_DAILY_LIMIT => {
+                    self.quota_exhausted.store(true, Ordering::Relaxed);
+                    return Err(AppError::Geocoder(response.message).into());
+                }
+                STATUS_QPS_LIMIT if attempt < Self::MAX_RETRIES => {
+                    let delay = self.throttle();
+                    warn!(
+                        "地址解析被限流：{}，所有请求暂停 {:?} ({}/{})",
+                        response.message,
+                        delay,
+                        attempt + 1,
+                        Self::MAX_RETRIES
+                    );
+                }
+                status if attempt < Self::MAX_RETRIES => {
+                    let delay = Self::retry_delay(attempt);
+                    info!(
+                        "地址解析失败，状态码: {}，消息: {}，将在 {:?} 后重试 ({}/{})",
+                        status,
+                        response.message,
+                        delay,
+                        attempt + 1,
+                        Self::MAX_RETRIES
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                _ => return Err(AppError::Geocoder(response.message).into()),
+            }
+            attempt += 1;
         }
+    }
 
-        let response = client
-            .get("https://apis.map.qq.com/ws/geocoder/v1/")
-            .query(&params)
+    async fn request(&self, params: &[(&str, &str)]) -> reqwest::Result<GeocoderResponse> {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.client
+            .get(&self.url)
+            .query(params)
             .send()
-            .await?;
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
 
-        let geocoder_response: GeocoderResponse = response.json().await?;
+    /// 单个请求失败（非限流）时第 `attempt` 次重试前的等待时间
+    fn retry_delay(attempt: i32) -> Duration {
+        Self::BASE_DELAY.mul_f32(1.5_f32.powi(attempt))
+    }
 
-        if geocoder_response.status == 0 {
-            if let Some(result) = geocoder_response.result {
-                return Ok(result.location);
+    /// 等待共享退避结束并取得令牌
+    async fn wait_for_turn(&self) {
+        loop {
+            let now = Instant::now();
+            let backoff_until = self.backoff.lock().unwrap().until;
+            let wait = match backoff_until {
+                Some(until) if until > now => Some(until - now),
+                _ => self.bucket.lock().unwrap().try_acquire(now),
+            };
+            match wait {
+                Some(wait) => tokio::time::sleep(wait).await,
+                None => return,
             }
-        } else if geocoder_response.status == 348 && !use_policy {
-            use_policy = true;
-            info!("收到状态码348，添加policy=1参数并立即重试");
-            continue;
-        } else if attempt < max_retries {
-            let delay = base_delay.mul_f32(1.5_f32.powi(attempt));
-            info!(
-                "地址解析失败，状态码: {}，消息: {}，将在 {:?} 后重试 ({}/{})",
-                geocoder_response.status,
-                geocoder_response.message,
-                delay,
-                attempt + 1,
-                max_retries
-            );
-
-            tokio::time::sleep(delay).await;
-            continue;
         }
+    }
 
-        return Err(AppError::Geocoder(geocoder_response.message).into());
+    /// 记录一次限流并延长共享退避时间，返回本次退避时长
+    fn throttle(&self) -> Duration {
+        let mut backoff = self.backoff.lock().unwrap();
+        let delay = Self::BASE_DELAY
+            .mul_f64(2_f64.powi(backoff.consecutive as i32))
+            .min(Self::MAX_BACKOFF);
+        backoff.consecutive += 1;
+        let until = Instant::now() + delay;
+        backoff.until = Some(backoff.until.map_or(until, |current| current.max(until)));
+        delay
     }
 
-    unreachable!("重试循环应该返回结果或错误");
+    fn reset_backoff(&self) {
+        self.backoff.lock().unwrap().consecutive = 0;
+    }
+}
+
+/// 使用环境变量中的配置解析单个地址
+pub async fn get_geo_location(address: &str) -> Result<GeoLocation> {
+    Geocoder::from_env().locate(address).await
 }
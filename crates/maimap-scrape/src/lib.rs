@@ -0,0 +1,9 @@
+pub mod closure_guard;
+pub mod geo_location;
+pub mod reconcile;
+pub mod scheduler;
+pub mod scrape;
+pub mod store_list;
+pub mod store_source;
+
+mod export_hashmap;
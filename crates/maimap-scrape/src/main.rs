@@ -1,342 +1,284 @@
-use std::str::FromStr;
-mod cleanup;
-mod export_hashmap;
-mod geo_location;
+mod cli;
 
-use crate::geo_location::get_geo_location;
-use headless_chrome::{Browser, LaunchOptions, Tab};
-use std::collections::{HashMap, HashSet};
+use clap::Parser;
+use futures::FutureExt;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
 
-use maimap_utils::backup::backup_database;
-use maimap_utils::db::{
-    DateTime, Decimal128, ensure_mongodb_connected, get_max_arcade_id, insert_many_arcades,
+use maimap_utils::backup::manifest::{verify_local_backup, verify_stored_backup};
+use maimap_utils::backup::retention::{RetentionPolicy, prune_backups};
+use maimap_utils::backup::store::{LocalStore, backup_store_from_env};
+use maimap_utils::backup::{
+    backup_database, download_backup, list_backups, resolve_backup, restore_database,
 };
-use maimap_utils::env::check_required_env_vars;
-use maimap_utils::errors::{AppError, Context, Result};
-use maimap_utils::types::Arcade;
-
-use crate::cleanup::{
-    convert_lat_lng_to_decimal128, convert_null_dead_to_bool, normalize_name,
-    remove_duplicate_arcades,
+use maimap_utils::db::{ensure_mongodb_connected, get_mongodb_client};
+use maimap_utils::env::{
+    DB_NAME, backup_path, check_required_env_vars, load_env_file, schedule_backup, schedule_scrape,
+    scheduler_jitter_secs, scheduler_lock_ttl_secs,
 };
-use crate::export_hashmap::export_arcade_names_to_files;
-use scraper::{Html, Selector};
-use std::time::Duration;
-use tracing::{error, info, warn};
+use maimap_utils::errors::Result;
+use maimap_utils::indexes::{check_indexes, ensure_indexes, index_specs};
+use maimap_utils::migrations::Migrator;
 
-#[tokio::main]
-async fn main() {
-    tracing_subscriber::fmt().init();
+use crate::cli::{Cli, Command, MigrateAction, SourceArgs};
+use maimap_scrape::geo_location::get_geo_location;
+use maimap_scrape::scheduler::{ScheduledJob, Scheduler};
+use maimap_scrape::scrape::{ScrapeOptions, export_arcade_names, scrape_arcades};
+use tracing::{error, info};
 
-    info!("执行定时爬取华立机厅任务");
-    check_required_env_vars();
-    ensure_mongodb_connected().await;
-    match remove_duplicate_arcades().await {
-        Ok(_) => {
-            info!("清理数据库完成！");
-        }
-        Err(e) => {
-            error!("清理数据库失败！{}", e);
-            return;
-        }
-    }
-    match convert_lat_lng_to_decimal128().await {
-        Ok(_) => {
-            info!("转换经纬度数据成功！");
-        }
-        Err(e) => {
-            error!("转换经纬度数据失败！{}", e);
-            return;
-        }
-    }
-    match convert_null_dead_to_bool().await {
-        Ok(_) => {
-            info!("转换 arcade_dead 数据成功！");
-        }
-        Err(e) => {
-            error!("转换 arcade_dead 数据失败！{}", e);
-            return;
-        }
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    tracing_subscriber::fmt()
+        .with_max_level(cli.log_level())
+        .init();
+
+    if let Some(config) = &cli.config {
+        load_env_file(config);
     }
-    match scrape_arcades().await {
-        Ok(_) => {
-            info!("爬取任务成功！");
+    check_required_env_vars();
+
+    let result = match cli.command {
+        None => run_all(SourceArgs::default().into_options(cli.dry_run)).await,
+        Some(Command::Scrape(source)) => scrape(source.into_options(cli.dry_run)).await,
+        Some(Command::Migrate { action }) => migrate(action, cli.dry_run).await,
+        Some(Command::Indexes) => indexes(cli.dry_run).await,
+        Some(Command::Backup) => backup(cli.dry_run).await,
+        Some(Command::Prune) => prune(cli.dry_run).await,
+        Some(Command::Restore {
+            archive,
+            list,
+            target_db,
+            drop,
+        }) => {
+            if list {
+                list_backup_files().await
+            } else {
+                restore(archive, target_db, drop, cli.dry_run).await
+            }
         }
+        Some(Command::Verify { archive }) => verify(archive).await,
+        Some(Command::ExportNames(source)) => export_names(source.into_options(cli.dry_run)).await,
+        Some(Command::Geocode { address }) => geocode(&address).await,
+        Some(Command::Daemon(source)) => daemon(source.into_options(cli.dry_run)).await,
+    };
+
+    match result {
+        Ok(_) => ExitCode::SUCCESS,
         Err(e) => {
-            error!("爬取任务失败！{}", e);
-            return;
+            error!("{:#}", e);
+            ExitCode::FAILURE
         }
     }
-
-    match backup_database().await {
-        Ok(_) => info!("备份数据库成功！"),
-        Err(e) => error!("备份数据库失败！{}", e),
-    }
 }
 
-pub async fn scrape_arcades() -> Result<()> {
-    info!("开始爬取华立官网机厅");
-    let content;
-    {
-        let browser = Browser::new(
-            LaunchOptions::default_builder()
-                .headless(true)
-                .sandbox(false)
-                .build()?,
-        )
-        .context("运行无头Chrome失败")?;
-        let tab = browser.new_tab().context("新建浏览器Tab失败")?;
-        tab.navigate_to("http://wc.wahlap.net/maidx/location/index.html")
-            .context("导航到指定页面失败")?;
-        tab.wait_until_navigated().context("等待指定页面加载失败")?;
-        wait_for_store_list(&tab).context("等待store_list加载失败")?;
-        content = tab.get_content().context("获取页面content失败")?;
-    }
-
-    let existing_arcades = get_existing_arcades().await?;
-    info!("从数据库获取到 {} 个已存在机厅", existing_arcades.len());
-
-    let web_arcades = parse_all_store_list(&content).await?;
-    info!("从网站解析到 {} 个机厅", web_arcades.len());
-
-    export_arcade_names_to_files(&existing_arcades, &web_arcades).await?;
-
-    process_arcade_data(existing_arcades, web_arcades).await
+/// 定时任务：执行数据库迁移、爬取机厅并备份
+async fn run_all(options: ScrapeOptions) -> Result<()> {
+    info!("执行定时爬取华立机厅任务");
+    migrate(MigrateAction::Up { to: None }, options.dry_run).await?;
+    let dry_run = options.dry_run;
+    scrape(options).await?;
+    backup(dry_run).await
 }
 
-async fn get_existing_arcades() -> Result<HashMap<String, Arcade>> {
-    use maimap_utils::db::get_all_arcades;
-
-    let arcades = get_all_arcades().await?;
-    let mut arcade_map = HashMap::new();
+async fn scrape(options: ScrapeOptions) -> Result<()> {
+    ensure_mongodb_connected().await;
+    scrape_arcades(&options).await?;
+    info!("爬取任务成功！");
+    Ok(())
+}
 
-    for mut arcade in arcades {
-        let normalized_name = normalize_name(&arcade.arcade_name);
-        if arcade_map.contains_key(&normalized_name) {
-            warn!(
-                "发现规范化后重复的机厅名称: '{}' (原名: '{}'), 将跳过此条目。",
-                normalized_name, arcade.arcade_name
+async fn migrate(action: MigrateAction, dry_run: bool) -> Result<()> {
+    ensure_mongodb_connected().await;
+    let migrator = Migrator::from_client();
+    match action {
+        MigrateAction::Status => {
+            for status in migrator.status().await? {
+                let applied_at = status
+                    .applied_at
+                    .and_then(|time| time.try_to_rfc3339_string().ok())
+                    .unwrap_or_else(|| "未执行".to_string());
+                println!("{:>4}  {:<32}  {}", status.version, status.name, applied_at);
+            }
+        }
+        MigrateAction::Up { to } => {
+            let versions = migrator.up(to, dry_run).await?;
+            info!("数据库迁移完成，共 {} 个：{:?}", versions.len(), versions);
+        }
+        MigrateAction::Down { to } => {
+            let versions = migrator.down(to, dry_run).await?;
+            info!(
+                "数据库迁移回滚完成，共 {} 个：{:?}",
+                versions.len(),
+                versions
             );
-            continue;
         }
-        arcade.arcade_name = normalized_name.clone();
-        arcade_map.insert(normalized_name, arcade);
     }
-
-    Ok(arcade_map)
+    Ok(())
 }
 
-fn wait_for_store_list(tab: &Tab) -> Result<()> {
-    const MIN_ARCADES: usize = 2000;
-    const MAX_RETRIES: u32 = 5;
-    const POLLING_TIMEOUT: Duration = Duration::from_secs(90);
-    const POLLING_INTERVAL: Duration = Duration::from_secs(2);
-
-    for attempt in 1..=MAX_RETRIES {
-        info!("开始加载机厅列表 (尝试 {}/{})", attempt, MAX_RETRIES);
-
-        if attempt > 1 {
-            info!("机厅数量不足，重新加载页面...");
-            tab.reload(false, None).context("页面重新加载失败")?;
-            tab.wait_until_navigated()
-                .context("等待页面重新加载后导航失败")?;
-        }
-
-        tab.wait_for_element_with_custom_timeout(".store_list", Duration::from_secs(60))
-            .context("等待 .store_list 容器元素超时")?;
-        info!("列表容器已出现，开始轮询检查机厅数量...");
-
-        let start_time = std::time::Instant::now();
-        loop {
-            let js_script = "document.querySelectorAll('.store_list li').length";
-            let result = tab
-                .evaluate(js_script, false)
-                .context("执行 JavaScript 数量检查失败")?;
-            let count = match result.value {
-                Some(serde_json::Value::Number(n)) => n.as_u64().unwrap_or(0) as usize,
-                _ => 0,
-            };
-            if count >= MIN_ARCADES {
-                info!("成功加载 {} 个机厅，数量符合预期。", count);
-                return Ok(());
-            }
-            if start_time.elapsed() > POLLING_TIMEOUT {
-                warn!(
-                    "轮询超时 ({}s)，当前加载了 {} 个机厅，未达到 {} 个。准备重试...",
-                    POLLING_TIMEOUT.as_secs(),
-                    count,
-                    MIN_ARCADES
-                );
-                break;
-            }
-
-            info!("当前已加载 {}/{} 个机厅，继续等待...", count, MIN_ARCADES);
-            std::thread::sleep(POLLING_INTERVAL);
-        }
+async fn indexes(dry_run: bool) -> Result<()> {
+    ensure_mongodb_connected().await;
+    let db = get_mongodb_client().database(DB_NAME);
+    let specs = index_specs();
+
+    let drifts = if dry_run {
+        check_indexes(&db, &specs).await?
+    } else {
+        ensure_indexes(&db, &specs).await?
+    };
+    for drift in &drifts {
+        println!("{}", drift);
     }
-
-    Err(AppError::Scrape(format!(
-        "经过 {} 次尝试后，仍未能加载到至少 {} 个机厅。",
-        MAX_RETRIES, MIN_ARCADES
-    ))
-    .into())
+    info!("索引检查完成，{} 处不一致", drifts.len());
+    Ok(())
 }
 
-async fn parse_all_store_list(html: &str) -> Result<Vec<(String, String)>> {
-    let document = Html::parse_document(html);
-    let mut arcade_info = Vec::new();
-
-    let ul_selector = Selector::parse(".store_list").map_err(|e| AppError::Parse(e.to_string()))?;
-    let li_selector = Selector::parse("li").map_err(|e| AppError::Parse(e.to_string()))?;
-    let store_name_selector =
-        Selector::parse("span.store_name").map_err(|e| AppError::Parse(e.to_string()))?;
-    let store_address_selector =
-        Selector::parse("span.store_address").map_err(|e| AppError::Parse(e.to_string()))?;
-
-    for store_list in document.select(&ul_selector) {
-        for li in store_list.select(&li_selector) {
-            let store_name_raw = li
-                .select(&store_name_selector)
-                .next()
-                .map(|el| el.text().collect::<String>())
-                .ok_or_else(|| AppError::Parse("store_name".to_string()))?;
-
-            let store_name = normalize_name(&store_name_raw);
-
-            let store_address = li
-                .select(&store_address_selector)
-                .next()
-                .map(|el| el.text().collect::<String>().trim().to_string())
-                .ok_or_else(|| AppError::Parse("store_address".to_string()))?;
+/// 备份数据库，之后按保留策略清理过期的备份
+async fn backup(dry_run: bool) -> Result<()> {
+    if dry_run {
+        info!("[dry-run] 跳过备份数据库");
+    } else {
+        ensure_mongodb_connected().await;
+        let filename = backup_database().await?;
+        info!("备份数据库成功！{}", filename);
+    }
+    prune(dry_run).await
+}
 
-            arcade_info.push((store_name, store_address));
-        }
+/// 按保留策略清理备份存储与备份目录中的过期备份
+async fn prune(dry_run: bool) -> Result<()> {
+    let policy = RetentionPolicy::from_env();
+    let store = backup_store_from_env()?;
+    prune_backups(store.as_ref(), &policy, dry_run).await?;
+
+    // mongodump 在备份目录中留下的副本；未设置备份目录时不清理，避免删除当前目录下的文件
+    let backup_dir = backup_path();
+    if backup_dir.is_empty() {
+        info!("未设置 BACKUP_PATH，跳过清理本地备份目录");
+        return Ok(());
     }
+    prune_backups(&LocalStore::new(backup_dir), &policy, dry_run).await?;
+    Ok(())
+}
 
-    Ok(arcade_info)
+async fn list_backup_files() -> Result<()> {
+    let store = backup_store_from_env()?;
+    for backup in list_backups(store.as_ref()).await? {
+        println!("{:<32}  {:>12}", backup.key, backup.size);
+    }
+    Ok(())
 }
 
-async fn process_arcade_data(
-    existing_arcades: HashMap<String, Arcade>,
-    web_arcades: Vec<(String, String)>,
+async fn restore(
+    archive: Option<String>,
+    target_db: Option<String>,
+    drop: bool,
+    dry_run: bool,
 ) -> Result<()> {
-    let time = DateTime::now();
-    let max_id = get_max_arcade_id().await?;
-
-    // 用于标记数据库中已处理的机厅
-    let mut processed_arcade_names = HashSet::new();
-    let mut arcades_to_update = Vec::new();
-    let mut new_arcades = Vec::new();
-    let mut id_counter = max_id;
-
-    // 处理网站上的机厅
-    for (name, address) in web_arcades {
-        if let Some(existing) = existing_arcades.get(&name) {
-            // 机厅已存在，检查地址是否变化或机厅是否已关闭
-            processed_arcade_names.insert(name.clone());
-
-            if existing.arcade_address != address {
-                // 地址有变动，需要获取新的地理位置
+    let target_db = target_db.unwrap_or_else(|| DB_NAME.to_string());
+
+    // 本地已有的备份文件直接恢复，否则从备份存储中下载
+    let local = archive
+        .as_deref()
+        .map(PathBuf::from)
+        .filter(|path| path.is_file());
+    let archive = match local {
+        Some(path) => path,
+        None => {
+            let store = backup_store_from_env()?;
+            let key = resolve_backup(store.as_ref(), archive.as_deref()).await?;
+            if dry_run {
                 info!(
-                    "机厅地址或状态有变，准备更新: {}，旧地址：{}，新地址：{}",
-                    name, existing.arcade_address, address
+                    "[dry-run] 将从{}下载 {} 并恢复到数据库 {}",
+                    store.describe(),
+                    key,
+                    target_db
                 );
-                let location = get_geo_location(&address).await?;
-                tokio::time::sleep(Duration::from_millis(1000)).await;
-
-                let updated = Arcade {
-                    arcade_id: existing.arcade_id,
-                    arcade_name: existing.arcade_name.clone(),
-                    arcade_address: address,
-                    arcade_dead: existing.arcade_dead,
-                    arcade_cost: existing.arcade_cost,
-                    arcade_count: existing.arcade_count,
-                    arcade_lat: Decimal128::from_str(&location.lat.to_string())?,
-                    arcade_lng: Decimal128::from_str(&location.lng.to_string())?,
-                    arcade_pos: Some(location.to_point()),
-                    created_at: existing.created_at,
-                };
-
-                arcades_to_update.push(updated);
-                info!("更新完成");
+                return Ok(());
             }
-        } else {
-            // 新机厅，需要获取地理位置
-            info!("发现新机厅，准备获取地理位置: {}", name);
-            let location = get_geo_location(&address).await?;
-            tokio::time::sleep(Duration::from_millis(1000)).await;
-
-            id_counter += 1;
-
-            let arcade = Arcade {
-                arcade_id: id_counter,
-                arcade_name: name.clone(),
-                arcade_address: address,
-                arcade_dead: false,
-                arcade_cost: None,
-                arcade_count: None,
-                arcade_lat: Decimal128::from_str(&location.lat.to_string())?,
-                arcade_lng: Decimal128::from_str(&location.lng.to_string())?,
-                arcade_pos: Some(location.to_point()),
-                created_at: time,
-            };
-
-            new_arcades.push(arcade);
-            info!("新增机厅：ID {}，名称 {}", id_counter, name);
-        }
-    }
-
-    // 标记已关闭的机厅
-    let mut closed_arcades = Vec::new();
-    for (name, arcade) in &existing_arcades {
-        if !processed_arcade_names.contains(name) && !arcade.arcade_dead {
-            let closed = Arcade {
-                arcade_id: arcade.arcade_id,
-                arcade_name: arcade.arcade_name.clone(),
-                arcade_address: arcade.arcade_address.clone(),
-                arcade_dead: true,
-                arcade_cost: arcade.arcade_cost,
-                arcade_count: arcade.arcade_count,
-                arcade_lat: arcade.arcade_lat,
-                arcade_lng: arcade.arcade_lng,
-                arcade_pos: arcade.arcade_pos.clone(),
-                created_at: arcade.created_at,
-            };
-            closed_arcades.push(closed);
-            info!("标记已关闭机厅：ID {}，名称 {}", arcade.arcade_id, name);
+            download_backup(store.as_ref(), &key).await?
         }
+    };
+
+    if dry_run {
+        info!(
+            "[dry-run] 将从 {} 恢复到数据库 {}",
+            archive.display(),
+            target_db
+        );
+        return Ok(());
     }
 
-    // 执行数据库操作
-    let arcades_to_update_len = arcades_to_update.len();
-    if !arcades_to_update.is_empty() {
-        update_arcades(&arcades_to_update).await?;
-    }
+    ensure_mongodb_connected().await;
+    let restored = restore_database(&archive, &target_db, drop).await?;
+    info!("恢复数据库成功！共恢复 {} 个集合", restored.len());
+    Ok(())
+}
 
-    let new_arcades_len = new_arcades.len();
-    if !new_arcades.is_empty() {
-        insert_many_arcades(new_arcades).await?;
-    }
+async fn verify(archive: Option<String>) -> Result<()> {
+    let local = archive
+        .as_deref()
+        .map(PathBuf::from)
+        .filter(|path| path.is_file());
+    let manifest = match local {
+        Some(path) => verify_local_backup(&path)?,
+        None => {
+            let store = backup_store_from_env()?;
+            let key = resolve_backup(store.as_ref(), archive.as_deref()).await?;
+            verify_stored_backup(store.as_ref(), &key).await?
+        }
+    };
 
-    let closed_arcades_len = closed_arcades.len();
-    if !closed_arcades.is_empty() {
-        update_arcades(&closed_arcades).await?;
+    for collection in &manifest.collections {
+        println!("{:<24}  {:>10}", collection.name, collection.count);
     }
-
     info!(
-        "处理完成：更新 {} 个机厅，新增 {} 个机厅，标记关闭 {} 个机厅",
-        arcades_to_update_len, new_arcades_len, closed_arcades_len
+        "备份 {} 校验通过：{} 字节，SHA-256 {}",
+        manifest.archive, manifest.size, manifest.sha256
     );
-
     Ok(())
 }
 
-async fn update_arcades(arcades: &[Arcade]) -> Result<()> {
-    use maimap_utils::db::update_arcade;
+async fn export_names(options: ScrapeOptions) -> Result<()> {
+    ensure_mongodb_connected().await;
+    export_arcade_names(&options).await
+}
 
-    for arcade in arcades {
-        update_arcade(arcade).await?;
+async fn geocode(address: &str) -> Result<()> {
+    let location = get_geo_location(address).await?;
+    println!("{}", location);
+    Ok(())
+}
+
+/// 常驻运行，启动时执行数据库迁移，之后按 cron 表达式定期爬取与备份
+async fn daemon(options: ScrapeOptions) -> Result<()> {
+    let dry_run = options.dry_run;
+    migrate(MigrateAction::Up { to: None }, dry_run).await?;
+    let options = Arc::new(options);
+
+    let mut jobs = Vec::new();
+    let expression = schedule_scrape();
+    if !expression.is_empty() {
+        jobs.push(ScheduledJob::new("scrape", &expression, move || {
+            let options = options.clone();
+            async move { scrape_arcades(&options).await }.boxed()
+        })?);
+    }
+    let expression = schedule_backup();
+    if !expression.is_empty() {
+        jobs.push(ScheduledJob::new("backup", &expression, move || {
+            backup(dry_run).boxed()
+        })?);
     }
 
-    Ok(())
+    Scheduler::new(
+        jobs,
+        Duration::from_secs(scheduler_jitter_secs()),
+        Duration::from_secs(scheduler_lock_ttl_secs()),
+    )
+    .run()
+    .await
 }
@@ -0,0 +1,199 @@
+use crate::geo_location::GeoLocation;
+use crate::store_list::WebArcade;
+use maimap_utils::env::{scrape_rename_review_threshold, scrape_rename_threshold};
+use maimap_utils::types::{Arcade, RenameReview};
+use std::collections::HashSet;
+
+/// 超过此距离（米）的两个地址不再提供位置上的相似度
+const MAX_MATCH_DISTANCE: f64 = 500.0;
+
+/// 网站上的新机厅与数据库中待关闭机厅之间的疑似改名
+pub struct RenameCandidate<'a> {
+    pub existing: &'a Arcade,
+    pub web: WebArcade,
+    /// 0 到 1 之间的置信度
+    pub confidence: f64,
+}
+
+impl RenameCandidate<'_> {
+    /// 保存到爬取记录中供人工确认的形式
+    pub fn to_review(&self) -> RenameReview {
+        RenameReview {
+            arcade_id: self.existing.arcade_id,
+            old_name: self.existing.arcade_name.clone(),
+            old_address: self.existing.arcade_address.clone(),
+            new_name: self.web.name.clone(),
+            new_address: self.web.address.clone(),
+            confidence: self.confidence,
+        }
+    }
+}
+
+/// 根据名称、地址与地理位置的相似度识别改名的机厅，保留原有的 arcade_id
+pub struct RenameMatcher {
+    /// 不低于此置信度的匹配直接视为改名
+    threshold: f64,
+    /// 不低于此置信度但未达到 `threshold` 的匹配只记录下来供人工确认
+    review_threshold: f64,
+}
+
+impl RenameMatcher {
+    pub fn new(threshold: f64, review_threshold: f64) -> Self {
+        Self {
+            threshold,
+            review_threshold: review_threshold.min(threshold),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(scrape_rename_threshold(), scrape_rename_review_threshold())
+    }
+
+    /// 计算置信度；`location` 为网站机厅地址解析出的坐标，未知时只比较名称与地址
+    pub fn confidence(
+        &self,
+        existing: &Arcade,
+        web: &WebArcade,
+        location: Option<&GeoLocation>,
+    ) -> f64 {
+        let name = similarity(&existing.arcade_name, &web.name);
+        let address = similarity(&existing.arcade_address, &web.address);
+
+        match location.and_then(|location| distance_to(existing, location)) {
+            Some(distance) => {
+                let proximity = (1.0 - distance / MAX_MATCH_DISTANCE).max(0.0);
+                0.4 * name + 0.3 * address + 0.3 * proximity
+            }
+            None => 0.5 * name + 0.5 * address,
+        }
+    }
+
+    /// 在新机厅与待关闭机厅之间按置信度从高到低贪心匹配。
+    ///
+    /// 返回达到阈值的改名，以及只达到复核阈值的候选；
+    /// 已匹配的机厅会从 `new_arcades` 与 `closing_arcades` 中移除。
+    pub fn match_renames<'a>(
+        &self,
+        new_arcades: &mut Vec<WebArcade>,
+        closing_arcades: &mut Vec<&'a Arcade>,
+    ) -> (Vec<RenameCandidate<'a>>, Vec<RenameCandidate<'a>>) {
+        let mut pairs = Vec::new();
+        for (web_index, web) in new_arcades.iter().enumerate() {
+            for (existing_index, existing) in closing_arcades.iter().enumerate() {
+                let confidence = self.confidence(existing, web, None);
+                if confidence >= self.review_threshold {
+                    pairs.push((confidence, web_index, existing_index));
+                }
+            }
+        }
+        pairs.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let mut used_web = HashSet::new();
+        let mut used_existing = HashSet::new();
+        let mut renames = Vec::new();
+        let mut proposals = Vec::new();
+        for (confidence, web_index, existing_index) in pairs {
+            if used_web.contains(&web_index) || used_existing.contains(&existing_index) {
+                continue;
+            }
+            let candidate = RenameCandidate {
+                existing: closing_arcades[existing_index],
+                web: new_arcades[web_index].clone(),
+                confidence,
+            };
+            if confidence >= self.threshold {
+                used_web.insert(web_index);
+                used_existing.insert(existing_index);
+                renames.push(candidate);
+            } else {
+                proposals.push(candidate);
+            }
+        }
+
+        *new_arcades = std::mem::take(new_arcades)
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| !used_web.contains(index))
+            .map(|(_, web)| web)
+            .collect();
+        *closing_arcades = std::mem::take(closing_arcades)
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| !used_existing.contains(index))
+            .map(|(_, existing)| existing)
+            .collect();
+
+        (renames, proposals)
+    }
+
+    /// 结合新机厅解析出的坐标，在待关闭机厅中寻找置信度最高的改名匹配
+    pub fn match_by_location(
+        &self,
+        web: &WebArcade,
+        location: &GeoLocation,
+        closing_arcades: &[&Arcade],
+    ) -> Option<(usize, f64)> {
+        closing_arcades
+            .iter()
+            .enumerate()
+            .map(|(index, existing)| (index, self.confidence(existing, web, Some(location))))
+            .filter(|(_, confidence)| *confidence >= self.threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+    }
+}
+
+/// 去掉空白与常见标点，避免括号、连字符的差异影响相似度
+fn strip_punctuation(s: &str) -> Vec<char> {
+    s.chars()
+        .filter(|c| {
+            !c.is_whitespace()
+                && !c.is_ascii_punctuation()
+                && !matches!(c, '（' | '）' | '·' | '、' | '，' | '。' | '－' | '—')
+        })
+        .collect()
+}
+
+/// 基于相邻字符对的 Dice 系数，适合中文名称与地址
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let a = strip_punctuation(a);
+    let b = strip_punctuation(b);
+    if a == b {
+        return 1.0;
+    }
+    if a.len() < 2 || b.len() < 2 {
+        return 0.0;
+    }
+
+    let bigrams = |chars: &[char]| -> Vec<(char, char)> {
+        chars.windows(2).map(|pair| (pair[0], pair[1])).collect()
+    };
+    let a_bigrams = bigrams(&a);
+    let mut b_bigrams = bigrams(&b);
+
+    let mut common = 0;
+    for bigram in &a_bigrams {
+        if let Some(position) = b_bigrams.iter().position(|other| other == bigram) {
+            b_bigrams.swap_remove(position);
+            common += 1;
+        }
+    }
+
+    2.0 * common as f64 / (a.len() + b.len() - 2) as f64
+}
+
+/// 机厅坐标到指定坐标的球面距离（米）
+fn distance_to(existing: &Arcade, location: &GeoLocation) -> Option<f64> {
+    const EARTH_RADIUS: f64 = 6_371_000.0;
+
+    let lat: f64 = existing.arcade_lat.to_string().parse().ok()?;
+    let lng: f64 = existing.arcade_lng.to_string().parse().ok()?;
+    if lat == 0.0 && lng == 0.0 {
+        return None;
+    }
+
+    let (lat1, lat2) = (lat.to_radians(), location.lat.to_radians());
+    let d_lat = lat2 - lat1;
+    let d_lng = (location.lng - lng).to_radians();
+    let h = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lng / 2.0).sin().powi(2);
+    Some(2.0 * EARTH_RADIUS * h.sqrt().asin())
+}
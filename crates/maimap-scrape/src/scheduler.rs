@@ -0,0 +1,189 @@
+use chrono::Local;
+use cron::Schedule;
+use futures::future::BoxFuture;
+use maimap_utils::db::{
+    Bson, DateTime, Document, doc, lock_owner, release_job_lock, renew_job_lock,
+    run_with_heartbeat, to_bson, try_acquire_job_lock, update_job_status,
+};
+use maimap_utils::errors::{AppError, Result};
+use maimap_utils::types::JobRunStatus;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+type JobFn = dyn Fn() -> BoxFuture<'static, Result<()>> + Send + Sync;
+
+/// 按 cron 表达式定期运行的任务
+pub struct ScheduledJob {
+    name: String,
+    expression: String,
+    schedule: Schedule,
+    run: Arc<JobFn>,
+}
+
+impl ScheduledJob {
+    /// `expression` 为带秒的 cron 表达式，例如 `0 0 4 * * *` 表示每天 4 点
+    pub fn new<F>(name: impl Into<String>, expression: &str, run: F) -> Result<Self>
+    where
+        F: Fn() -> BoxFuture<'static, Result<()>> + Send + Sync + 'static,
+    {
+        let name = name.into();
+        let schedule = Schedule::from_str(expression).map_err(|e| {
+            AppError::Configuration(format!(
+                "任务 {} 的 cron 表达式 '{}' 无效：{}",
+                name, expression, e
+            ))
+        })?;
+        Ok(Self {
+            name,
+            expression: expression.to_string(),
+            schedule,
+            run: Arc::new(run),
+        })
+    }
+}
+
+/// 在一个或多个实例上运行定时任务。
+///
+/// 每次运行前在计划时间上加入随机延迟，并通过数据库中的任务锁
+/// 保证同一计划时间的任务只在一个实例上执行。
+pub struct Scheduler {
+    jobs: Vec<ScheduledJob>,
+    jitter: Duration,
+    lock_ttl: Duration,
+    owner: String,
+}
+
+impl Scheduler {
+    pub fn new(jobs: Vec<ScheduledJob>, jitter: Duration, lock_ttl: Duration) -> Self {
+        Self {
+            jobs,
+            jitter,
+            lock_ttl,
+            owner: lock_owner(),
+        }
+    }
+
+    /// 运行全部任务，直到进程退出
+    pub async fn run(self) -> Result<()> {
+        if self.jobs.is_empty() {
+            return Err(AppError::Configuration("没有启用任何定时任务".to_string()).into());
+        }
+
+        let owner: Arc<str> = self.owner.into();
+        let mut handles = Vec::new();
+        for job in self.jobs {
+            info!("已启用定时任务 {}：{}", job.name, job.expression);
+            handles.push(tokio::spawn(run_job(
+                job,
+                self.jitter,
+                self.lock_ttl,
+                owner.clone(),
+            )));
+        }
+
+        for handle in handles {
+            handle.await?;
+        }
+        Ok(())
+    }
+}
+
+async fn run_job(job: ScheduledJob, jitter: Duration, lock_ttl: Duration, owner: Arc<str>) {
+    loop {
+        let Some(slot) = job.schedule.upcoming(Local).next() else {
+            warn!("任务 {} 之后不会再运行", job.name);
+            return;
+        };
+        let run_at = slot + random_jitter(jitter);
+        info!("任务 {} 下一次运行时间：{}", job.name, run_at);
+        record_status(
+            &job,
+            doc! { "next_run_at": DateTime::from_millis(run_at.timestamp_millis()) },
+        )
+        .await;
+
+        let delay = (run_at - Local::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(delay).await;
+
+        let slot = DateTime::from_millis(slot.timestamp_millis());
+        match try_acquire_job_lock(&job.name, slot, &owner, lock_ttl).await {
+            Ok(true) => {}
+            Ok(false) => {
+                info!("任务 {} 已由其他实例运行，跳过本次", job.name);
+                continue;
+            }
+            Err(e) => {
+                error!("获取任务 {} 的锁失败：{:#}", job.name, e);
+                continue;
+            }
+        }
+
+        info!("开始运行任务 {}", job.name);
+        record_status(
+            &job,
+            doc! {
+                "last_started_at": DateTime::now(),
+                "last_status": to_bson_status(JobRunStatus::Running),
+                "last_owner": owner.as_ref(),
+            },
+        )
+        .await;
+
+        // 运行期间定期续期任务锁，避免运行时间超过有效期后被其他实例重复执行
+        let result = run_with_heartbeat((job.run)(), lock_ttl / 3, || {
+            renew_lock(&job.name, &owner, lock_ttl)
+        })
+        .await;
+        let (status, last_error) = match &result {
+            Ok(_) => {
+                info!("任务 {} 运行成功", job.name);
+                (JobRunStatus::Succeeded, None)
+            }
+            Err(e) => {
+                error!("任务 {} 运行失败：{:#}", job.name, e);
+                (JobRunStatus::Failed, Some(format!("{:#}", e)))
+            }
+        };
+        record_status(
+            &job,
+            doc! {
+                "last_finished_at": DateTime::now(),
+                "last_status": to_bson_status(status),
+                "last_error": last_error,
+            },
+        )
+        .await;
+
+        if let Err(e) = release_job_lock(&job.name, &owner).await {
+            warn!("释放任务 {} 的锁失败：{:#}", job.name, e);
+        }
+    }
+}
+
+/// 续期失败时任务继续运行，只记录日志
+async fn renew_lock(job: &str, owner: &str, ttl: Duration) {
+    match renew_job_lock(job, owner, ttl).await {
+        Ok(true) => {}
+        Ok(false) => warn!("任务 {} 的锁已失效，其他实例可能会重复运行", job),
+        Err(e) => warn!("续期任务 {} 的锁失败：{:#}", job, e),
+    }
+}
+
+fn random_jitter(max: Duration) -> chrono::Duration {
+    let millis = rand::random_range(0..=max.as_millis() as i64);
+    chrono::Duration::milliseconds(millis)
+}
+
+fn to_bson_status(status: JobRunStatus) -> Bson {
+    to_bson(&status).unwrap_or(Bson::Null)
+}
+
+/// 任务状态只用于展示，写入失败时仅记录日志
+async fn record_status(job: &ScheduledJob, mut update: Document) {
+    update.insert("schedule", &job.expression);
+    if let Err(e) = update_job_status(&job.name, update).await {
+        warn!("更新任务 {} 的状态失败：{:#}", job.name, e);
+    }
+}
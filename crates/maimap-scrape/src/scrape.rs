@@ -0,0 +1,664 @@
+use crate::closure_guard::ClosureGuard;
+use crate::export_hashmap::export_arcade_names_to_files;
+use crate::geo_location::{GeoLocation, Geocoder};
+use crate::reconcile::{RenameCandidate, RenameMatcher};
+use crate::store_list::{WebArcade, normalize_name};
+use crate::store_source::{
+    ChromeStoreSource, FallbackStoreSource, HttpStoreSource, SnapshotStoreSource, StoreSource,
+    StoreSourceKind, archive_store_list_page,
+};
+use futures::{StreamExt, TryStreamExt};
+use maimap_utils::db::{
+    ArcadeWrites, DateTime, Decimal128, allocate_arcade_ids, apply_arcade_writes,
+    get_geocode_cache, get_geocode_queue, insert_scrape_run, record_geocode_failure,
+    save_geocode_cache,
+};
+use maimap_utils::env::{
+    geocoder_max_attempts, geocoder_retry_after_hours, scrape_closure_grace_runs,
+    scrape_min_arcades, wahlap_store_api_url,
+};
+use maimap_utils::errors::Result;
+use maimap_utils::types::{
+    Arcade, ArcadeEvent, ArcadeEventKind, GeocodeCacheEntry, GeocodeFailure, ScrapeRun,
+};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::str::FromStr;
+use tracing::{info, warn};
+
+/// 爬取任务的选项
+#[derive(Clone, Default)]
+pub struct ScrapeOptions {
+    /// 只输出将要执行的操作，不写入数据库
+    pub dry_run: bool,
+    /// 访问华立官网时使用的数据来源
+    pub source: StoreSourceKind,
+    /// 从保存的页面快照读取机厅列表，而不是访问华立官网
+    pub from_html: Option<PathBuf>,
+    /// 将抓取到的页面按时间戳保存到此目录
+    pub archive_dir: Option<PathBuf>,
+}
+
+/// 爬取华立官网机厅并同步到数据库，非 dry-run 时将运行记录保存到 `scrape_runs`。
+pub async fn scrape_arcades(options: &ScrapeOptions) -> Result<()> {
+    let mut run = ScrapeRun::start();
+    let result = run_scrape(options, &mut run).await;
+    run.finish(result.as_ref().err().map(|e| format!("{:#}", e)));
+
+    if !options.dry_run
+        && let Err(e) = insert_scrape_run(&run).await
+    {
+        warn!("保存爬取记录失败：{}", e);
+    }
+
+    result
+}
+
+async fn run_scrape(options: &ScrapeOptions, run: &mut ScrapeRun) -> Result<()> {
+    let web_arcades = fetch_web_arcades(options).await?;
+    run.page_arcade_count = web_arcades.len() as i32;
+
+    let existing_arcades = get_existing_arcades().await?;
+    info!("从数据库获取到 {} 个已存在机厅", existing_arcades.len());
+
+    process_arcade_data(existing_arcades, web_arcades, options.dry_run, run).await
+}
+
+/// 仅导出数据库与网站的机厅名称，不修改数据库。
+pub async fn export_arcade_names(options: &ScrapeOptions) -> Result<()> {
+    let web_arcades = fetch_web_arcades(options).await?;
+    let existing_arcades = get_existing_arcades().await?;
+    export_arcade_names_to_files(&existing_arcades, &web_arcades).await
+}
+
+async fn fetch_web_arcades(options: &ScrapeOptions) -> Result<Vec<WebArcade>> {
+    let page = store_source(options)?.fetch().await?;
+    if options.from_html.is_none()
+        && let Some(dir) = &options.archive_dir
+    {
+        archive_store_list_page(&page, dir).await?;
+    }
+
+    info!("从网站解析到 {} 个机厅", page.arcades.len());
+    Ok(page.arcades)
+}
+
+fn store_source(options: &ScrapeOptions) -> Result<Box<dyn StoreSource>> {
+    if let Some(path) = &options.from_html {
+        return Ok(Box::new(SnapshotStoreSource::new(path)));
+    }
+
+    info!("开始爬取华立官网机厅");
+    let min_arcades = scrape_min_arcades();
+    Ok(match options.source {
+        StoreSourceKind::Http => Box::new(FallbackStoreSource::new(vec![
+            Box::new(HttpStoreSource::new(wahlap_store_api_url(), min_arcades)?),
+            Box::new(ChromeStoreSource::new(min_arcades)),
+        ])),
+        StoreSourceKind::Chrome => Box::new(ChromeStoreSource::new(min_arcades)),
+    })
+}
+
+/// 网站机厅列表与数据库比对后的变更
+pub struct ArcadeChanges<'a> {
+    /// 去重后网站上的机厅数量
+    pub web_arcade_count: usize,
+    /// 数据库中不存在的机厅
+    pub new_arcades: Vec<WebArcade>,
+    /// 信息发生变化的机厅及其在网站上的最新信息
+    pub updated_arcades: Vec<(&'a Arcade, WebArcade)>,
+    /// 已标记关闭、但重新出现在网站上的机厅及其最新信息
+    pub reopened_arcades: Vec<(&'a Arcade, WebArcade)>,
+    /// 网站上已不存在、需要标记关闭的机厅
+    pub closing_arcades: Vec<&'a Arcade>,
+    /// 网站上已不存在、但连续缺失次数尚未达到关闭要求的机厅
+    pub missing_arcades: Vec<&'a Arcade>,
+    /// 置信度不足以自动处理、需要人工确认的疑似改名
+    pub proposed_renames: Vec<RenameCandidate<'a>>,
+}
+
+/// 比对网站与数据库中的机厅，不访问数据库或地图接口。
+///
+/// 优先按华立店铺ID匹配，没有店铺ID时按规范化后的名称匹配；
+/// 剩余的新机厅与待关闭机厅再按名称与地址的相似度识别改名。
+/// 存活机厅需连续 `closure_grace_runs` 次未出现在网站上才会被标记关闭。
+pub fn plan_arcade_changes<'a>(
+    existing_arcades: &'a HashMap<String, Arcade>,
+    web_arcades: &[WebArcade],
+    matcher: &RenameMatcher,
+    closure_grace_runs: i32,
+) -> ArcadeChanges<'a> {
+    let by_store_id: HashMap<&str, &Arcade> = existing_arcades
+        .values()
+        .filter_map(|arcade| {
+            arcade
+                .arcade_store_id
+                .as_deref()
+                .map(|store_id| (store_id, arcade))
+        })
+        .collect();
+
+    let mut matched_arcade_ids = HashSet::new();
+    let mut new_arcade_keys = HashSet::new();
+    let mut new_arcades = Vec::new();
+    let mut updated_arcades = Vec::new();
+    let mut reopened_arcades = Vec::new();
+
+    for web in web_arcades {
+        let existing = web
+            .store_id
+            .as_deref()
+            .and_then(|store_id| by_store_id.get(store_id).copied())
+            .or_else(|| {
+                existing_arcades
+                    .get(&web.name)
+                    .filter(|existing| existing.arcade_store_id.is_none() || web.store_id.is_none())
+            });
+
+        match existing {
+            Some(existing) => {
+                if !matched_arcade_ids.insert(existing.arcade_id) {
+                    continue;
+                }
+                if existing.arcade_dead {
+                    reopened_arcades.push((existing, web.clone()));
+                } else if needs_update(existing, web) {
+                    updated_arcades.push((existing, web.clone()));
+                }
+            }
+            None => {
+                let key = web.store_id.as_deref().unwrap_or(&web.name);
+                if new_arcade_keys.insert(key) {
+                    new_arcades.push(web.clone());
+                }
+            }
+        }
+    }
+
+    let web_arcade_count = matched_arcade_ids.len() + new_arcades.len();
+
+    let mut unmatched_arcades: Vec<&Arcade> = existing_arcades
+        .values()
+        .filter(|arcade| !matched_arcade_ids.contains(&arcade.arcade_id) && !arcade.arcade_dead)
+        .collect();
+    unmatched_arcades.sort_by_key(|arcade| arcade.arcade_id);
+
+    let (renames, proposed_renames) =
+        matcher.match_renames(&mut new_arcades, &mut unmatched_arcades);
+    for rename in renames {
+        info!(
+            "识别到机厅改名（置信度 {:.2}）：ID {}，{} => {}",
+            rename.confidence,
+            rename.existing.arcade_id,
+            rename.existing.arcade_name,
+            rename.web.name
+        );
+        updated_arcades.push((rename.existing, rename.web));
+    }
+
+    let (closing_arcades, missing_arcades) = unmatched_arcades
+        .into_iter()
+        .partition(|arcade| should_close(arcade, closure_grace_runs));
+
+    ArcadeChanges {
+        web_arcade_count,
+        new_arcades,
+        updated_arcades,
+        reopened_arcades,
+        closing_arcades,
+        missing_arcades,
+        proposed_renames,
+    }
+}
+
+/// 算上本次，机厅连续缺失的次数是否已达到关闭要求
+fn should_close(arcade: &Arcade, closure_grace_runs: i32) -> bool {
+    arcade.arcade_missing_count + 1 >= closure_grace_runs
+}
+
+fn needs_update(existing: &Arcade, web: &WebArcade) -> bool {
+    fn changed(current: &Option<String>, latest: &Option<String>) -> bool {
+        latest.is_some() && current != latest
+    }
+
+    existing.arcade_missing_count > 0
+        || existing.arcade_name != web.name
+        || existing.arcade_address != web.address
+        || changed(&existing.arcade_store_id, &web.store_id)
+        || changed(&existing.arcade_province, &web.province)
+        || changed(&existing.arcade_machine, &web.machine)
+}
+
+/// 用网站上的最新信息覆盖机厅的名称、地址与店铺信息，网站未提供的字段保持不变
+fn apply_web_arcade(existing: &Arcade, web: WebArcade) -> Arcade {
+    Arcade {
+        arcade_missing_count: 0,
+        arcade_name: web.name,
+        arcade_address: web.address,
+        arcade_store_id: web.store_id.or_else(|| existing.arcade_store_id.clone()),
+        arcade_province: web.province.or_else(|| existing.arcade_province.clone()),
+        arcade_machine: web.machine.or_else(|| existing.arcade_machine.clone()),
+        ..existing.clone()
+    }
+}
+
+async fn get_existing_arcades() -> Result<HashMap<String, Arcade>> {
+    use maimap_utils::db::get_all_arcades;
+
+    let arcades = get_all_arcades().await?;
+    let mut arcade_map = HashMap::new();
+
+    for mut arcade in arcades {
+        let normalized_name = normalize_name(&arcade.arcade_name);
+        if arcade_map.contains_key(&normalized_name) {
+            warn!(
+                "发现规范化后重复的机厅名称: '{}' (原名: '{}'), 将跳过此条目。",
+                normalized_name, arcade.arcade_name
+            );
+            continue;
+        }
+        arcade.arcade_name = normalized_name.clone();
+        arcade_map.insert(normalized_name, arcade);
+    }
+
+    Ok(arcade_map)
+}
+
+async fn process_arcade_data(
+    existing_arcades: HashMap<String, Arcade>,
+    web_arcades: Vec<WebArcade>,
+    dry_run: bool,
+    run: &mut ScrapeRun,
+) -> Result<()> {
+    let time = DateTime::now();
+
+    let matcher = RenameMatcher::from_env();
+    let closure_grace_runs = scrape_closure_grace_runs();
+    let changes = plan_arcade_changes(
+        &existing_arcades,
+        &web_arcades,
+        &matcher,
+        closure_grace_runs,
+    );
+    run.parsed_arcade_count = changes.web_arcade_count as i32;
+    // 疑似改名随爬取记录保存，dry-run 时只输出到日志
+    for candidate in &changes.proposed_renames {
+        info!(
+            "疑似改名，需人工确认（置信度 {:.2}）：ID {}，{} => {}",
+            candidate.confidence,
+            candidate.existing.arcade_id,
+            candidate.existing.arcade_name,
+            candidate.web.name
+        );
+    }
+    run.rename_reviews = changes
+        .proposed_renames
+        .iter()
+        .map(RenameCandidate::to_review)
+        .collect();
+
+    // 在获取地理位置之前检查待关闭机厅的数量，避免页面加载不完整时误关闭
+    let alive_count = existing_arcades
+        .values()
+        .filter(|arcade| !arcade.arcade_dead)
+        .count();
+    ClosureGuard::from_env().check(&changes.closing_arcades, alive_count, run)?;
+
+    if dry_run {
+        for (existing, web) in &changes.updated_arcades {
+            info!(
+                "[dry-run] 将更新机厅：ID {}，名称 {}，地址：{}",
+                existing.arcade_id, web.name, web.address
+            );
+        }
+        for (existing, web) in &changes.reopened_arcades {
+            info!(
+                "[dry-run] 将重新开放机厅：ID {}，名称 {}，地址：{}",
+                existing.arcade_id, web.name, web.address
+            );
+        }
+        for web in &changes.new_arcades {
+            info!("[dry-run] 将新增机厅: {}，地址：{}", web.name, web.address);
+        }
+        for arcade in &changes.closing_arcades {
+            info!(
+                "[dry-run] 将标记关闭机厅：ID {}，名称 {}",
+                arcade.arcade_id, arcade.arcade_name
+            );
+        }
+        for arcade in &changes.missing_arcades {
+            info!(
+                "[dry-run] 机厅本次未出现在网站上（连续 {} 次）：ID {}，名称 {}",
+                arcade.arcade_missing_count + 1,
+                arcade.arcade_id,
+                arcade.arcade_name
+            );
+        }
+        info!(
+            "[dry-run] 处理完成：将更新 {} 个机厅，重新开放 {} 个机厅，新增 {} 个机厅，标记关闭 {} 个机厅，暂缓关闭 {} 个机厅",
+            changes.updated_arcades.len(),
+            changes.reopened_arcades.len(),
+            changes.new_arcades.len(),
+            changes.closing_arcades.len(),
+            changes.missing_arcades.len()
+        );
+        info!("[dry-run] 未解析新地址，按坐标识别的改名不会在此列出，这些机厅显示为新增与关闭");
+        return Ok(());
+    }
+
+    // 并发解析所有新地址，解析失败的机厅本次跳过
+    let addresses = changes
+        .updated_arcades
+        .iter()
+        .chain(&changes.reopened_arcades)
+        .filter(|(existing, web)| existing.arcade_address != web.address)
+        .map(|(_, web)| web)
+        .chain(&changes.new_arcades)
+        .map(|web| (web.address.clone(), web.name.clone()))
+        .collect();
+    let locations = locate_all(&Geocoder::from_env(), addresses, run).await?;
+
+    let mut arcades_to_update = Vec::new();
+    let mut new_arcades = Vec::new();
+    let mut events = Vec::new();
+
+    let updated_arcades = changes.updated_arcades.into_iter().map(|u| (u, false));
+    let reopened_arcades = changes.reopened_arcades.into_iter().map(|r| (r, true));
+    for ((existing, web), reopened) in updated_arcades.chain(reopened_arcades) {
+        let address_changed = existing.arcade_address != web.address;
+        let mut updated = apply_web_arcade(existing, web);
+
+        if reopened {
+            info!(
+                "已关闭的机厅重新出现，准备恢复：ID {}，名称 {}",
+                existing.arcade_id, updated.arcade_name
+            );
+            updated.arcade_dead = false;
+        }
+
+        if address_changed {
+            // 地址有变动，需要获取新的地理位置
+            info!(
+                "机厅地址或状态有变，准备更新: {}，旧地址：{}，新地址：{}",
+                updated.arcade_name, existing.arcade_address, updated.arcade_address
+            );
+            let Some(location) = locations.get(&updated.arcade_address) else {
+                continue;
+            };
+
+            updated.arcade_lat = Decimal128::from_str(&location.lat.to_string())?;
+            updated.arcade_lng = Decimal128::from_str(&location.lng.to_string())?;
+            updated.arcade_pos = Some(location.to_point());
+        } else {
+            info!(
+                "机厅信息有变，准备更新：ID {}，名称 {}",
+                existing.arcade_id, updated.arcade_name
+            );
+        }
+
+        if reopened {
+            events.push(ArcadeEvent::new(&updated, ArcadeEventKind::Reopened, time));
+            run.reopened_arcade_ids.push(updated.arcade_id);
+        } else {
+            run.updated_arcade_ids.push(updated.arcade_id);
+        }
+        arcades_to_update.push(updated);
+        info!("更新完成");
+    }
+
+    // 新机厅，需要获取地理位置
+    let mut unmatched_arcades: Vec<&Arcade> = changes
+        .closing_arcades
+        .into_iter()
+        .chain(changes.missing_arcades)
+        .collect();
+    for web in changes.new_arcades {
+        info!("发现新机厅，准备获取地理位置: {}", web.name);
+        let Some(location) = locations.get(&web.address) else {
+            continue;
+        };
+
+        // 坐标与待关闭机厅相近时视为改名，沿用原机厅ID
+        if let Some((index, confidence)) =
+            matcher.match_by_location(&web, location, &unmatched_arcades)
+        {
+            let existing = unmatched_arcades.remove(index);
+            info!(
+                "根据位置识别到机厅改名（置信度 {:.2}）：ID {}，{} => {}",
+                confidence, existing.arcade_id, existing.arcade_name, web.name
+            );
+            let mut updated = apply_web_arcade(existing, web);
+            updated.arcade_lat = Decimal128::from_str(&location.lat.to_string())?;
+            updated.arcade_lng = Decimal128::from_str(&location.lng.to_string())?;
+            updated.arcade_pos = Some(location.to_point());
+            run.updated_arcade_ids.push(updated.arcade_id);
+            arcades_to_update.push(updated);
+            continue;
+        }
+
+        // ID 在全部新机厅确定后统一分配
+        let arcade = Arcade {
+            arcade_id: 0,
+            arcade_name: web.name,
+            arcade_address: web.address,
+            arcade_dead: false,
+            arcade_cost: None,
+            arcade_count: None,
+            arcade_lat: Decimal128::from_str(&location.lat.to_string())?,
+            arcade_lng: Decimal128::from_str(&location.lng.to_string())?,
+            arcade_pos: Some(location.to_point()),
+            arcade_store_id: web.store_id,
+            arcade_province: web.province,
+            arcade_machine: web.machine,
+            arcade_missing_count: 0,
+            created_at: time,
+        };
+
+        new_arcades.push(arcade);
+    }
+
+    // 从计数器原子地分配新机厅的ID，避免与其他写入者冲突
+    let ids = allocate_arcade_ids(new_arcades.len() as i32).await?;
+    for (arcade, id) in new_arcades.iter_mut().zip(ids) {
+        arcade.arcade_id = id;
+        info!("新增机厅：ID {}，名称 {}", id, arcade.arcade_name);
+        run.new_arcade_ids.push(id);
+    }
+
+    // 标记已关闭的机厅，未达到连续缺失次数的只累加计数
+    let mut closed_arcades = Vec::new();
+    let mut missing_arcades = Vec::new();
+    for arcade in unmatched_arcades {
+        let missing_count = arcade.arcade_missing_count + 1;
+        if should_close(arcade, closure_grace_runs) {
+            info!(
+                "标记已关闭机厅：ID {}，名称 {}",
+                arcade.arcade_id, arcade.arcade_name
+            );
+            events.push(ArcadeEvent::new(arcade, ArcadeEventKind::Closed, time));
+            run.closed_arcade_ids.push(arcade.arcade_id);
+            closed_arcades.push(Arcade {
+                arcade_dead: true,
+                arcade_missing_count: missing_count,
+                ..arcade.clone()
+            });
+        } else {
+            info!(
+                "机厅本次未出现在网站上（连续 {}/{} 次）：ID {}，名称 {}",
+                missing_count, closure_grace_runs, arcade.arcade_id, arcade.arcade_name
+            );
+            missing_arcades.push(Arcade {
+                arcade_missing_count: missing_count,
+                ..arcade.clone()
+            });
+        }
+    }
+
+    // 所有变更一次性写入数据库
+    let arcades_to_update_len = arcades_to_update.len();
+    let new_arcades_len = new_arcades.len();
+    let closed_arcades_len = closed_arcades.len();
+    let missing_arcades_len = missing_arcades.len();
+
+    let mut replaced = arcades_to_update;
+    replaced.extend(closed_arcades);
+    replaced.extend(missing_arcades);
+    apply_arcade_writes(&ArcadeWrites {
+        replaced,
+        inserted: new_arcades,
+        events,
+    })
+    .await?;
+
+    info!(
+        "处理完成：更新 {} 个机厅，新增 {} 个机厅，标记关闭 {} 个机厅，暂缓关闭 {} 个机厅",
+        arcades_to_update_len, new_arcades_len, closed_arcades_len, missing_arcades_len
+    );
+
+    Ok(())
+}
+
+/// 一个地址的解析结果
+enum Located {
+    Cached(GeoLocation),
+    Fetched(GeoLocation),
+    Failed(String),
+}
+
+/// 本次需要请求的地址，以及失败次数过多而放弃的地址
+#[derive(Debug, Default)]
+pub struct GeocodePlan {
+    /// 待解析的地址与机厅名，重试队列中的地址排在前面
+    pub addresses: Vec<(String, String)>,
+    /// 累计失败次数已达上限、且未到重试时间的地址
+    pub given_up: Vec<GeocodeFailure>,
+}
+
+/// 合并重试队列与本次需要解析的地址，去除重复的地址。
+///
+/// 队列中的地址即使本次不再需要也会重试，成功的结果写入缓存供之后使用；
+/// 累计失败 `max_attempts` 次的地址暂停请求，直到最近一次尝试早于 `retry_before` 时再重试一次，
+/// 删除 `geocode_queue` 中的记录可立即重新尝试。
+pub fn plan_geocode_requests(
+    queue: Vec<GeocodeFailure>,
+    addresses: Vec<(String, String)>,
+    max_attempts: i32,
+    retry_before: DateTime,
+) -> GeocodePlan {
+    let needed: HashSet<_> = addresses
+        .iter()
+        .map(|(address, _)| address.clone())
+        .collect();
+    let mut plan = GeocodePlan::default();
+    let mut seen = HashSet::new();
+    for failure in queue {
+        if !seen.insert(failure.address.clone()) {
+            continue;
+        }
+        if failure.attempts >= max_attempts && failure.last_attempt_at > retry_before {
+            // 只报告本次仍需要坐标的地址，其余的留在队列中不再处理
+            if needed.contains(&failure.address) {
+                plan.given_up.push(failure);
+            }
+        } else {
+            plan.addresses.push((failure.address, failure.arcade_name));
+        }
+    }
+    plan.addresses.extend(
+        addresses
+            .into_iter()
+            .filter(|(address, _)| seen.insert(address.clone())),
+    );
+    plan
+}
+
+/// 并发解析地址，优先使用此前缓存的结果，返回地址到坐标的映射。
+///
+/// 先读取重试队列，上次失败的地址与本次的新地址一同解析。
+/// 解析失败的地址会加入重试队列且不出现在结果中，由调用方跳过对应机厅，
+/// 下次爬取时会重新尝试；只有数据库错误会中止爬取。
+async fn locate_all(
+    geocoder: &Geocoder,
+    addresses: Vec<(String, String)>,
+    run: &mut ScrapeRun,
+) -> Result<HashMap<String, GeoLocation>> {
+    let queue = get_geocode_queue().await?;
+    let retry_after = geocoder_retry_after_hours() * 60 * 60 * 1000;
+    let retry_before = DateTime::from_millis(DateTime::now().timestamp_millis() - retry_after);
+    let plan = plan_geocode_requests(queue, addresses, geocoder_max_attempts(), retry_before);
+    for failure in plan.given_up {
+        warn!(
+            "机厅 {} 的地址 {} 已连续解析失败 {} 次，暂不重试：{}",
+            failure.arcade_name, failure.address, failure.attempts, failure.error
+        );
+        run.errors.push(format!(
+            "{}（{}）：已失败 {} 次，暂不重试：{}",
+            failure.arcade_name, failure.address, failure.attempts, failure.error
+        ));
+    }
+
+    let addresses = plan.addresses;
+    if !addresses.is_empty() {
+        info!("开始解析 {} 个地址", addresses.len());
+    }
+
+    let results: Vec<_> = futures::stream::iter(addresses)
+        .map(|(address, name)| async move {
+            let located = locate(geocoder, &address, &name).await?;
+            Ok::<_, maimap_utils::errors::Error>((address, name, located))
+        })
+        .buffer_unordered(geocoder.concurrency())
+        .try_collect()
+        .await?;
+
+    let mut locations = HashMap::new();
+    for (address, name, located) in results {
+        match located {
+            Located::Cached(location) => {
+                locations.insert(address, location);
+            }
+            Located::Fetched(location) => {
+                locations.insert(address, location);
+            }
+            Located::Failed(error) => {
+                run.errors
+                    .push(format!("{}（{}）：{}", name, address, error));
+            }
+        }
+    }
+
+    run.geocoder_calls = geocoder.request_count() as i32;
+
+    Ok(locations)
+}
+
+async fn locate(geocoder: &Geocoder, address: &str, name: &str) -> Result<Located> {
+    if let Some(entry) = get_geocode_cache(address).await? {
+        return Ok(Located::Cached(GeoLocation {
+            lat: entry.lat,
+            lng: entry.lng,
+        }));
+    }
+
+    match geocoder.locate(address).await {
+        Ok(location) => {
+            save_geocode_cache(&GeocodeCacheEntry {
+                address: address.to_string(),
+                lat: location.lat,
+                lng: location.lng,
+                updated_at: DateTime::now(),
+            })
+            .await?;
+            Ok(Located::Fetched(location))
+        }
+        Err(e) => {
+            let error = format!("{:#}", e);
+            warn!(
+                "解析机厅 {} 的地址 {} 失败，下次爬取时重试：{}",
+                name, address, error
+            );
+            record_geocode_failure(address, name, &error).await?;
+            Ok(Located::Failed(error))
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use headless_chrome::{Browser, LaunchOptions, Tab};
+use maimap_utils::errors::{AppError, Context, Result};
+use scraper::{Html, Selector};
+use serde_json::{Map, Value};
+use std::time::Duration;
+use tracing::{info, warn};
+
+const STORE_LIST_URL: &str = "http://wc.wahlap.net/maidx/location/index.html";
+
+/// 使用无头 Chrome 加载华立官网机厅列表页面，返回页面 HTML。
+pub(crate) fn fetch_store_list_html(min_arcades: usize) -> Result<String> {
+    let browser = Browser::new(
+        LaunchOptions::default_builder()
+            .headless(true)
+            .sandbox(false)
+            .build()?,
+    )
+    .context("运行无头Chrome失败")?;
+    let tab = browser.new_tab().context("新建浏览器Tab失败")?;
+    tab.navigate_to(STORE_LIST_URL)
+        .context("导航到指定页面失败")?;
+    tab.wait_until_navigated().context("等待指定页面加载失败")?;
+    wait_for_store_list(&tab, min_arcades).context("等待store_list加载失败")?;
+    tab.get_content().context("获取页面content失败")
+}
+
+fn wait_for_store_list(tab: &Tab, min_arcades: usize) -> Result<()> {
+    const MAX_RETRIES: u32 = 5;
+    const POLLING_TIMEOUT: Duration = Duration::from_secs(90);
+    const POLLING_INTERVAL: Duration = Duration::from_secs(2);
+
+    for attempt in 1..=MAX_RETRIES {
+        info!("开始加载机厅列表 (尝试 {}/{})", attempt, MAX_RETRIES);
+
+        if attempt > 1 {
+            info!("机厅数量不足，重新加载页面...");
+            tab.reload(false, None).context("页面重新加载失败")?;
+            tab.wait_until_navigated()
+                .context("等待页面重新加载后导航失败")?;
+        }
+
+        tab.wait_for_element_with_custom_timeout(".store_list", Duration::from_secs(60))
+            .context("等待 .store_list 容器元素超时")?;
+        info!("列表容器已出现，开始轮询检查机厅数量...");
+
+        let start_time = std::time::Instant::now();
+        loop {
+            let js_script = "document.querySelectorAll('.store_list li').length";
+            let result = tab
+                .evaluate(js_script, false)
+                .context("执行 JavaScript 数量检查失败")?;
+            let count = match result.value {
+                Some(serde_json::Value::Number(n)) => n.as_u64().unwrap_or(0) as usize,
+                _ => 0,
+            };
+            if count >= min_arcades {
+                info!("成功加载 {} 个机厅，数量符合预期。", count);
+                return Ok(());
+            }
+            if start_time.elapsed() > POLLING_TIMEOUT {
+                warn!(
+                    "轮询超时 ({}s)，当前加载了 {} 个机厅，未达到 {} 个。准备重试...",
+                    POLLING_TIMEOUT.as_secs(),
+                    count,
+                    min_arcades
+                );
+                break;
+            }
+
+            info!("当前已加载 {}/{} 个机厅，继续等待...", count, min_arcades);
+            std::thread::sleep(POLLING_INTERVAL);
+        }
+    }
+
+    Err(AppError::Scrape(format!(
+        "经过 {} 次尝试后，仍未能加载到至少 {} 个机厅。",
+        MAX_RETRIES, min_arcades
+    ))
+    .into())
+}
+
+/// 将机厅名中的全角字母、符号与空格转换为半角，并去掉首尾空白
+pub fn normalize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            // 将全角ASCII字符（！到～）转换为半角
+            '\u{FF01}'..='\u{FF5E}' => unsafe { char::from_u32_unchecked((c as u32) - 0xFEE0) },
+            // 将全角空格转换成半角空格
+            '\u{3000}' => ' ',
+            _ => c,
+        })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// 华立官网机厅列表中的一个机厅
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WebArcade {
+    /// 规范化后的机厅名
+    pub name: String,
+    pub address: String,
+    /// 华立店铺ID
+    pub store_id: Option<String>,
+    /// 所在省份
+    pub province: Option<String>,
+    /// 机台信息，官网接口中为机台数量
+    pub machine: Option<String>,
+}
+
+fn selector(css: &str) -> Result<Selector> {
+    Selector::parse(css).map_err(|e| AppError::Parse(e.to_string()).into())
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// 解析机厅列表页面，返回规范化后的机厅信息。
+///
+/// 页面只提供机厅名与地址，店铺ID、省份与机台信息只能从机厅列表接口（`--source http`）获取。
+pub fn parse_all_store_list(html: &str) -> Result<Vec<WebArcade>> {
+    let document = Html::parse_document(html);
+    let mut arcade_info = Vec::new();
+
+    let ul_selector = selector(".store_list")?;
+    let li_selector = selector("li")?;
+    let store_name_selector = selector("span.store_name")?;
+    let store_address_selector = selector("span.store_address")?;
+
+    for store_list in document.select(&ul_selector) {
+        for li in store_list.select(&li_selector) {
+            let store_name_raw = li
+                .select(&store_name_selector)
+                .next()
+                .map(|el| el.text().collect::<String>())
+                .ok_or_else(|| AppError::Parse("store_name".to_string()))?;
+
+            let store_name = normalize_name(&store_name_raw);
+
+            let store_address = li
+                .select(&store_address_selector)
+                .next()
+                .map(|el| el.text().collect::<String>().trim().to_string())
+                .ok_or_else(|| AppError::Parse("store_address".to_string()))?;
+
+            arcade_info.push(WebArcade {
+                name: store_name,
+                address: store_address,
+                ..Default::default()
+            });
+        }
+    }
+
+    Ok(arcade_info)
+}
+
+/// 将接口中可能为数字或字符串的字段转换为字符串
+fn value_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => non_empty(s),
+        Value::Null => None,
+        other => Some(other.to_string()),
+    }
+}
+
+/// 取记录中的一个字段，缺失或为空时返回 `None`
+fn record_value(record: &Map<String, Value>, name: &str) -> Option<String> {
+    record.get(name).and_then(value_to_string)
+}
+
+/// 解析官网机厅列表接口返回的 JSON，返回规范化后的机厅信息。
+///
+/// 接口的每条记录包含 `id`、`arcadeName`、`address`、`province` 与 `machineCount` 等字段，
+/// `machineCount` 记录为机台信息。
+pub fn parse_store_json(json: &str) -> Result<Vec<WebArcade>> {
+    let records: Vec<Map<String, Value>> =
+        serde_json::from_str(json).map_err(|e| AppError::Parse(e.to_string()))?;
+
+    records
+        .iter()
+        .map(|record| {
+            let name = record_value(record, "arcadeName")
+                .ok_or_else(|| AppError::Parse("arcadeName".to_string()))?;
+            let address = record_value(record, "address")
+                .ok_or_else(|| AppError::Parse("address".to_string()))?;
+            Ok(WebArcade {
+                name: normalize_name(&name),
+                address,
+                store_id: record_value(record, "id"),
+                province: record_value(record, "province"),
+                machine: record_value(record, "machineCount"),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,230 @@
+use crate::store_list::{WebArcade, fetch_store_list_html, parse_all_store_list, parse_store_json};
+use async_trait::async_trait;
+use clap::ValueEnum;
+use maimap_utils::errors::{AppError, Context, Result};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+/// 访问华立官网时使用的数据来源
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum StoreSourceKind {
+    /// 请求机厅列表接口，失败时退回无头 Chrome
+    #[default]
+    Http,
+    /// 只使用无头 Chrome
+    Chrome,
+}
+
+/// 机厅列表的原始内容格式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PageFormat {
+    Html,
+    Json,
+}
+
+impl PageFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            PageFormat::Html => "html",
+            PageFormat::Json => "json",
+        }
+    }
+
+    fn parse(self, raw: &str) -> Result<Vec<WebArcade>> {
+        match self {
+            PageFormat::Html => parse_all_store_list(raw),
+            PageFormat::Json => parse_store_json(raw),
+        }
+    }
+}
+
+/// 从数据来源获取到的机厅列表
+pub struct StoreListPage {
+    /// 原始页面或接口响应，用于存档
+    pub raw: String,
+    pub format: PageFormat,
+    /// 规范化后的机厅信息
+    pub arcades: Vec<WebArcade>,
+}
+
+impl StoreListPage {
+    pub fn parse(raw: String, format: PageFormat) -> Result<Self> {
+        let arcades = format.parse(&raw)?;
+        Ok(Self {
+            raw,
+            format,
+            arcades,
+        })
+    }
+}
+
+/// 华立机厅列表的数据来源
+#[async_trait]
+pub trait StoreSource: Send + Sync {
+    /// 来源名称，用于日志
+    fn name(&self) -> &str;
+
+    async fn fetch(&self) -> Result<StoreListPage>;
+}
+
+/// 直接请求华立页面自身使用的机厅列表接口
+pub struct HttpStoreSource {
+    client: reqwest::Client,
+    url: String,
+    min_arcades: usize,
+}
+
+impl HttpStoreSource {
+    pub fn new(url: impl Into<String>, min_arcades: usize) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()?;
+        Ok(Self {
+            client,
+            url: url.into(),
+            min_arcades,
+        })
+    }
+}
+
+#[async_trait]
+impl StoreSource for HttpStoreSource {
+    fn name(&self) -> &str {
+        "http"
+    }
+
+    async fn fetch(&self) -> Result<StoreListPage> {
+        info!("请求机厅列表接口：{}", self.url);
+        let raw = self
+            .client
+            .get(&self.url)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+
+        let page = StoreListPage::parse(raw, PageFormat::Json)?;
+        if page.arcades.len() < self.min_arcades {
+            return Err(AppError::Scrape(format!(
+                "接口仅返回 {} 个机厅，未达到 {} 个",
+                page.arcades.len(),
+                self.min_arcades
+            ))
+            .into());
+        }
+
+        Ok(page)
+    }
+}
+
+/// 使用无头 Chrome 加载机厅列表页面
+pub struct ChromeStoreSource {
+    min_arcades: usize,
+}
+
+impl ChromeStoreSource {
+    pub fn new(min_arcades: usize) -> Self {
+        Self { min_arcades }
+    }
+}
+
+#[async_trait]
+impl StoreSource for ChromeStoreSource {
+    fn name(&self) -> &str {
+        "chrome"
+    }
+
+    async fn fetch(&self) -> Result<StoreListPage> {
+        let min_arcades = self.min_arcades;
+        let raw = tokio::task::spawn_blocking(move || fetch_store_list_html(min_arcades)).await??;
+        StoreListPage::parse(raw, PageFormat::Html)
+    }
+}
+
+/// 读取此前保存的页面快照，按扩展名区分 HTML 与 JSON
+pub struct SnapshotStoreSource {
+    path: PathBuf,
+}
+
+impl SnapshotStoreSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl StoreSource for SnapshotStoreSource {
+    fn name(&self) -> &str {
+        "snapshot"
+    }
+
+    async fn fetch(&self) -> Result<StoreListPage> {
+        info!("从页面快照读取机厅列表：{:?}", self.path);
+        let raw = tokio::fs::read_to_string(&self.path)
+            .await
+            .with_context(|| format!("读取页面快照 {:?} 失败", self.path))?;
+        let format = match self.path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => PageFormat::Json,
+            _ => PageFormat::Html,
+        };
+        StoreListPage::parse(raw, format)
+    }
+}
+
+/// 依次尝试多个来源，返回第一个成功的结果
+pub struct FallbackStoreSource {
+    sources: Vec<Box<dyn StoreSource>>,
+}
+
+impl FallbackStoreSource {
+    pub fn new(sources: Vec<Box<dyn StoreSource>>) -> Self {
+        Self { sources }
+    }
+}
+
+#[async_trait]
+impl StoreSource for FallbackStoreSource {
+    fn name(&self) -> &str {
+        "fallback"
+    }
+
+    async fn fetch(&self) -> Result<StoreListPage> {
+        let mut last_error = None;
+        for source in &self.sources {
+            match source.fetch().await {
+                Ok(page) => return Ok(page),
+                Err(e) => {
+                    warn!("从 {} 获取机厅列表失败：{:#}", source.name(), e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error
+            .unwrap_or_else(|| AppError::Scrape("没有可用的机厅列表来源".to_string()).into()))
+    }
+}
+
+/// 将抓取到的页面按时间戳保存到指定目录，便于日后回放。
+pub async fn archive_store_list_page(page: &StoreListPage, dir: &Path) -> Result<PathBuf> {
+    tokio::fs::create_dir_all(dir)
+        .await
+        .with_context(|| format!("创建页面存档目录 {:?} 失败", dir))?;
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| AppError::TimestampGeneration(e.to_string()))?
+        .as_secs();
+    let path = dir.join(format!(
+        "store_list_{}.{}",
+        timestamp,
+        page.format.extension()
+    ));
+    tokio::fs::write(&path, &page.raw)
+        .await
+        .with_context(|| format!("写入页面存档 {:?} 失败", path))?;
+    info!("已存档机厅列表页面：{:?}", path);
+
+    Ok(path)
+}
@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod tests {
+    use maimap_scrape::closure_guard::ClosureGuard;
+    use maimap_utils::db::{DateTime, Decimal128};
+    use maimap_utils::types::{Arcade, ScrapeRun};
+    use std::str::FromStr;
+
+    fn arcade(id: i32) -> Arcade {
+        Arcade {
+            arcade_address: format!("地址{}", id),
+            arcade_cost: None,
+            arcade_count: None,
+            arcade_dead: false,
+            arcade_id: id,
+            arcade_lat: Decimal128::from_str("39.9").unwrap(),
+            arcade_lng: Decimal128::from_str("116.4").unwrap(),
+            arcade_pos: None,
+            arcade_name: format!("机厅{}", id),
+            arcade_store_id: None,
+            arcade_province: None,
+            arcade_machine: None,
+            arcade_missing_count: 0,
+            created_at: DateTime::now(),
+        }
+    }
+
+    #[test]
+    fn test_closure_guard_within_limits() {
+        let guard = ClosureGuard::new(0.05, 100, false);
+        assert!(!guard.exceeds(0, 2000));
+        assert!(!guard.exceeds(100, 2000));
+        assert!(guard.allows(100, 2000));
+    }
+
+    #[test]
+    fn test_closure_guard_count_limit() {
+        // 比例很宽松，只有数量上限生效
+        let guard = ClosureGuard::new(1.0, 10, false);
+        assert!(!guard.exceeds(10, 20));
+        assert!(guard.exceeds(11, 20));
+        assert!(!guard.allows(11, 20));
+    }
+
+    #[test]
+    fn test_closure_guard_ratio_limit() {
+        // 数量上限很宽松，只有比例生效
+        let guard = ClosureGuard::new(0.05, 10_000, false);
+        assert!(!guard.exceeds(5, 100));
+        assert!(guard.exceeds(6, 100));
+        assert!(!guard.allows(6, 100));
+        // 没有存活机厅时不按比例判断
+        assert!(!guard.exceeds(6, 0));
+    }
+
+    #[test]
+    fn test_closure_guard_override() {
+        let guard = ClosureGuard::new(0.05, 10, true);
+        assert!(guard.exceeds(500, 1000));
+        assert!(guard.allows(500, 1000));
+    }
+
+    #[test]
+    fn test_closure_guard_check_records_candidates() {
+        let arcades = [arcade(1), arcade(2), arcade(3)];
+        let closing: Vec<&Arcade> = arcades.iter().collect();
+
+        let mut run = ScrapeRun::start();
+        let guard = ClosureGuard::new(1.0, 2, false);
+        assert!(guard.check(&closing, 100, &mut run).is_err());
+        assert_eq!(run.closure_candidate_ids, vec![1, 2, 3]);
+
+        let mut run = ScrapeRun::start();
+        let guard = ClosureGuard::new(1.0, 2, true);
+        assert!(guard.check(&closing, 100, &mut run).is_ok());
+        assert_eq!(run.closure_candidate_ids, vec![1, 2, 3]);
+
+        let mut run = ScrapeRun::start();
+        let guard = ClosureGuard::new(1.0, 10, false);
+        assert!(guard.check(&closing, 100, &mut run).is_ok());
+        assert!(run.closure_candidate_ids.is_empty());
+    }
+}
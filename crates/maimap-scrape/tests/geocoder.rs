@@ -0,0 +1,169 @@
+#[cfg(test)]
+mod tests {
+    use maimap_scrape::geo_location::{Geocoder, TokenBucket};
+    use maimap_scrape::scrape::plan_geocode_requests;
+    use maimap_utils::db::DateTime;
+    use maimap_utils::types::GeocodeFailure;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::time::Instant;
+
+    const OK: &str =
+        r#"{"status":0,"message":"query ok","result":{"location":{"lat":39.9,"lng":116.4}}}"#;
+    const QPS_LIMIT: &str = r#"{"status":120,"message":"此key每秒请求量已达到上限"}"#;
+    const DAILY_LIMIT: &str = r#"{"status":121,"message":"此key每日调用量已达到上限"}"#;
+
+    fn failure(address: &str, attempts: i32) -> GeocodeFailure {
+        GeocodeFailure {
+            address: address.to_string(),
+            arcade_name: format!("{}的机厅", address),
+            error: "无法解析".to_string(),
+            attempts,
+            last_attempt_at: DateTime::now(),
+        }
+    }
+
+    /// 一天前
+    fn yesterday() -> DateTime {
+        DateTime::from_millis(DateTime::now().timestamp_millis() - 24 * 60 * 60 * 1000)
+    }
+
+    fn address(address: &str) -> (String, String) {
+        (address.to_string(), format!("{}的机厅", address))
+    }
+
+    /// 启动依次返回 `responses` 的本地地址解析服务，返回其地址。
+    /// `None` 表示读取请求后直接断开连接，不返回任何内容。
+    async fn serve_responses(responses: Vec<Option<String>>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 4096];
+                let _ = stream.read(&mut buf).await.unwrap();
+                if let Some(response) = response {
+                    stream.write_all(response.as_bytes()).await.unwrap();
+                }
+            }
+        });
+        format!("http://{}/ws/geocoder/v1/", addr)
+    }
+
+    fn response(status: &str, body: &str) -> Option<String> {
+        Some(format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        ))
+    }
+
+    /// 启动依次返回 `bodies` 的本地地址解析服务，返回其地址
+    async fn serve(bodies: Vec<&'static str>) -> String {
+        serve_responses(
+            bodies
+                .into_iter()
+                .map(|body| response("200 OK", body))
+                .collect(),
+        )
+        .await
+    }
+
+    #[test]
+    fn test_token_bucket() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2.0);
+
+        assert_eq!(bucket.try_acquire(start), None);
+        assert_eq!(bucket.try_acquire(start), None);
+        let wait = bucket.try_acquire(start).unwrap();
+        assert!(wait > Duration::from_millis(400) && wait <= Duration::from_millis(500));
+
+        assert_eq!(bucket.try_acquire(start + Duration::from_millis(500)), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_geocoder_backoff_on_throttle() {
+        let url = serve(vec![QPS_LIMIT, OK]).await;
+        let geocoder = Geocoder::new(url, "key", 100.0, 2);
+
+        let location = geocoder.locate("北京市东城区").await.unwrap();
+        assert_eq!(location.lat, 39.9);
+        assert_eq!(location.lng, 116.4);
+    }
+
+    #[tokio::test]
+    async fn test_geocoder_daily_limit() {
+        let url = serve(vec![DAILY_LIMIT]).await;
+        let geocoder = Geocoder::new(url, "key", 100.0, 2);
+
+        assert!(geocoder.locate("北京市东城区").await.is_err());
+        // 达到每日上限后不再发送请求
+        assert!(geocoder.locate("上海市徐汇区").await.is_err());
+        assert_eq!(geocoder.request_count(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_geocoder_retries_transport_errors() {
+        let url = serve_responses(vec![
+            None,
+            response("502 Bad Gateway", "<html>Bad Gateway</html>"),
+            response("200 OK", "不是JSON"),
+            response("200 OK", OK),
+        ])
+        .await;
+        let geocoder = Geocoder::new(url, "key", 100.0, 2);
+
+        let location = geocoder.locate("北京市东城区").await.unwrap();
+        assert_eq!(location.lat, 39.9);
+        assert_eq!(geocoder.request_count(), 4);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_geocoder_gives_up_after_retries() {
+        let url = serve_responses(vec![None, None, None, None]).await;
+        let geocoder = Geocoder::new(url, "key", 100.0, 2);
+
+        assert!(geocoder.locate("北京市东城区").await.is_err());
+    }
+
+    #[test]
+    fn test_plan_geocode_requests_retries_queue_first() {
+        let queue = vec![failure("队列地址", 1), failure("新地址", 2)];
+        let plan = plan_geocode_requests(
+            queue,
+            vec![address("新地址"), address("另一地址")],
+            5,
+            yesterday(),
+        );
+        assert_eq!(
+            plan.addresses,
+            vec![address("队列地址"), address("新地址"), address("另一地址")]
+        );
+        assert!(plan.given_up.is_empty());
+    }
+
+    #[test]
+    fn test_plan_geocode_requests_caps_attempts() {
+        let queue = vec![failure("放弃的地址", 5), failure("不再需要的地址", 9)];
+        let plan = plan_geocode_requests(queue, vec![address("放弃的地址")], 5, yesterday());
+        assert!(plan.addresses.is_empty());
+        // 本次不需要的地址不会被报告
+        assert_eq!(plan.given_up.len(), 1);
+        assert_eq!(plan.given_up[0].address, "放弃的地址");
+    }
+
+    #[test]
+    fn test_plan_geocode_requests_retries_after_delay() {
+        let mut stale = failure("很久之前失败的地址", 7);
+        stale.last_attempt_at = DateTime::from_millis(0);
+        let queue = vec![stale, failure("刚失败的地址", 5)];
+        let plan = plan_geocode_requests(queue, vec![address("刚失败的地址")], 5, yesterday());
+        // 最近一次尝试早于重试时间的地址再重试一次
+        assert_eq!(plan.addresses, vec![address("很久之前失败的地址")]);
+        assert_eq!(plan.given_up.len(), 1);
+        assert_eq!(plan.given_up[0].address, "刚失败的地址");
+    }
+}
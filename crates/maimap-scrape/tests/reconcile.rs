@@ -0,0 +1,156 @@
+#[cfg(test)]
+mod tests {
+    use maimap_scrape::geo_location::GeoLocation;
+    use maimap_scrape::reconcile::{RenameMatcher, similarity};
+    use maimap_scrape::scrape::plan_arcade_changes;
+    use maimap_scrape::store_list::WebArcade;
+    use maimap_utils::db::{DateTime, Decimal128};
+    use maimap_utils::types::Arcade;
+    use std::collections::HashMap;
+    use std::str::FromStr;
+
+    fn arcade(id: i32, name: &str, address: &str, lat: &str, lng: &str) -> Arcade {
+        Arcade {
+            arcade_address: address.to_string(),
+            arcade_cost: None,
+            arcade_count: None,
+            arcade_dead: false,
+            arcade_id: id,
+            arcade_lat: Decimal128::from_str(lat).unwrap(),
+            arcade_lng: Decimal128::from_str(lng).unwrap(),
+            arcade_pos: None,
+            arcade_name: name.to_string(),
+            arcade_store_id: None,
+            arcade_province: None,
+            arcade_machine: None,
+            arcade_missing_count: 0,
+            created_at: DateTime::now(),
+        }
+    }
+
+    fn web(name: &str, address: &str) -> WebArcade {
+        WebArcade {
+            name: name.to_string(),
+            address: address.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity("XX电玩城(万达店)", "XX电玩城（万达店）"), 1.0);
+        assert_eq!(similarity("XX电玩城(万达店)", "XX电玩城万达广场店"), 0.75);
+        assert!(similarity("XX电玩城(万达店)", "风云再起(徐家汇店)") < 0.2);
+    }
+
+    #[test]
+    fn test_plan_detects_rename() {
+        let existing_arcades: HashMap<_, _> = [
+            arcade(
+                10,
+                "XX电玩城(万达店)",
+                "江苏省南京市建邺区江东中路98号万达广场3层",
+                "32.03",
+                "118.73",
+            ),
+            arcade(
+                11,
+                "毫不相关的机厅",
+                "浙江省杭州市西湖区某路1号",
+                "30.2",
+                "120.1",
+            ),
+        ]
+        .into_iter()
+        .map(|arcade| (arcade.arcade_name.clone(), arcade))
+        .collect();
+        let web_arcades = vec![web(
+            "XX电玩城万达广场店",
+            "江苏省南京市建邺区江东中路98号万达广场3层",
+        )];
+
+        let changes = plan_arcade_changes(
+            &existing_arcades,
+            &web_arcades,
+            &RenameMatcher::new(0.75, 0.5),
+            1,
+        );
+
+        assert!(changes.new_arcades.is_empty());
+        assert_eq!(changes.updated_arcades.len(), 1);
+        assert_eq!(changes.updated_arcades[0].0.arcade_id, 10);
+        assert_eq!(changes.updated_arcades[0].1.name, "XX电玩城万达广场店");
+        let closing_ids: Vec<_> = changes
+            .closing_arcades
+            .iter()
+            .map(|arcade| arcade.arcade_id)
+            .collect();
+        assert_eq!(closing_ids, vec![11]);
+    }
+
+    #[test]
+    fn test_plan_proposes_uncertain_rename() {
+        let existing_arcades: HashMap<_, _> = [arcade(
+            10,
+            "XX电玩城(万达店)",
+            "江苏省南京市建邺区江东中路98号万达广场3层",
+            "32.03",
+            "118.73",
+        )]
+        .into_iter()
+        .map(|arcade| (arcade.arcade_name.clone(), arcade))
+        .collect();
+        let web_arcades = vec![web("XX电玩城万达广场店", "江苏省南京市建邺区江东中路100号")];
+
+        let changes = plan_arcade_changes(
+            &existing_arcades,
+            &web_arcades,
+            &RenameMatcher::new(0.75, 0.5),
+            1,
+        );
+
+        // 置信度不足时仍按新增与关闭处理，同时记录候选供人工确认
+        assert_eq!(changes.new_arcades.len(), 1);
+        assert_eq!(changes.closing_arcades.len(), 1);
+        assert_eq!(changes.proposed_renames.len(), 1);
+        assert_eq!(changes.proposed_renames[0].existing.arcade_id, 10);
+
+        let review = changes.proposed_renames[0].to_review();
+        assert_eq!(review.arcade_id, 10);
+        assert_eq!(review.old_name, "XX电玩城(万达店)");
+        assert_eq!(review.new_name, "XX电玩城万达广场店");
+        assert_eq!(review.confidence, changes.proposed_renames[0].confidence);
+    }
+
+    #[test]
+    fn test_match_by_location() {
+        let matcher = RenameMatcher::new(0.75, 0.5);
+        let nearby = arcade(
+            10,
+            "XX电玩城(万达店)",
+            "江东中路98号万达广场3层",
+            "32.0300",
+            "118.7300",
+        );
+        let far_away = arcade(
+            11,
+            "XX电玩城(万达店)",
+            "江东中路98号万达广场3层",
+            "31.2300",
+            "121.4700",
+        );
+        let closing = vec![&far_away, &nearby];
+        let web = web("XX电玩城万达广场店", "建邺区江东中路98号");
+        let location = GeoLocation {
+            lat: 32.0301,
+            lng: 118.7302,
+        };
+
+        let (index, confidence) = matcher
+            .match_by_location(&web, &location, &closing)
+            .unwrap();
+        assert_eq!(closing[index].arcade_id, 10);
+        assert!(confidence >= 0.75);
+        assert!(matcher.confidence(&far_away, &web, Some(&location)) < 0.75);
+    }
+}
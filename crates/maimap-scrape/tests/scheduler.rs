@@ -0,0 +1,12 @@
+#[cfg(test)]
+mod tests {
+    use futures::FutureExt;
+    use maimap_scrape::scheduler::ScheduledJob;
+
+    #[test]
+    fn test_scheduled_job_expression() {
+        assert!(ScheduledJob::new("scrape", "0 0 4 * * *", || async { Ok(()) }.boxed()).is_ok());
+        assert!(ScheduledJob::new("backup", "0 30 3 * * Mon", || async { Ok(()) }.boxed()).is_ok());
+        assert!(ScheduledJob::new("cleanup", "每天四点", || async { Ok(()) }.boxed()).is_err());
+    }
+}
@@ -0,0 +1,252 @@
+#[cfg(test)]
+mod tests {
+    use maimap_scrape::reconcile::RenameMatcher;
+    use maimap_scrape::scrape::plan_arcade_changes;
+    use maimap_scrape::store_list::{WebArcade, parse_all_store_list, parse_store_json};
+    use maimap_utils::db::{DateTime, Decimal128};
+    use maimap_utils::types::Arcade;
+    use std::collections::HashMap;
+    use std::str::FromStr;
+
+    const STORE_LIST_HTML: &str = include_str!("fixtures/store_list.html");
+    const STORE_LIST_JSON: &str = include_str!("fixtures/store_list.json");
+    const REST_LOCATION_JSON: &str = include_str!("fixtures/rest_location.json");
+
+    fn arcade(id: i32, name: &str, address: &str, dead: bool) -> Arcade {
+        Arcade {
+            arcade_address: address.to_string(),
+            arcade_cost: None,
+            arcade_count: None,
+            arcade_dead: dead,
+            arcade_id: id,
+            arcade_lat: Decimal128::from_str("39.9").unwrap(),
+            arcade_lng: Decimal128::from_str("116.4").unwrap(),
+            arcade_pos: None,
+            arcade_name: name.to_string(),
+            arcade_store_id: None,
+            arcade_province: None,
+            arcade_machine: None,
+            arcade_missing_count: 0,
+            created_at: DateTime::now(),
+        }
+    }
+
+    fn existing(arcades: Vec<Arcade>) -> HashMap<String, Arcade> {
+        arcades
+            .into_iter()
+            .map(|arcade| (arcade.arcade_name.clone(), arcade))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_store_list_snapshot() {
+        let arcades = parse_all_store_list(STORE_LIST_HTML).unwrap();
+        assert_eq!(arcades.len(), 5);
+        assert_eq!(
+            arcades[0],
+            WebArcade {
+                name: "环游嘉年华(王府井店)".to_string(),
+                address: "北京市东城区王府井大街138号新东安广场5层".to_string(),
+                ..Default::default()
+            }
+        );
+        // 全角字母与全角空格被规范化
+        assert_eq!(arcades[1].name, "SEGA 电玩城 西单店");
+    }
+
+    #[test]
+    fn test_parse_store_list_missing_address() {
+        let html =
+            r#"<ul class="store_list"><li><span class="store_name">只有名字</span></li></ul>"#;
+        assert!(parse_all_store_list(html).is_err());
+    }
+
+    #[test]
+    fn test_parse_store_json() {
+        let arcades = parse_store_json(STORE_LIST_JSON).unwrap();
+        assert_eq!(arcades.len(), 3);
+        assert_eq!(arcades[0].name, "环游嘉年华(王府井店)");
+        assert_eq!(arcades[0].store_id.as_deref(), Some("1001"));
+        assert_eq!(arcades[2].province.as_deref(), Some("上海市"));
+    }
+
+    #[test]
+    fn test_parse_rest_location_json() {
+        // 店铺ID只取 id，不与 placeId 混用
+        let arcades = parse_store_json(REST_LOCATION_JSON).unwrap();
+        assert_eq!(arcades.len(), 3);
+        assert_eq!(arcades[0].store_id.as_deref(), Some("1001"));
+        assert_eq!(
+            arcades[0].address,
+            "北京市东城区王府井大街138号新东安广场5层"
+        );
+        assert_eq!(arcades[1].name, "SEGA 电玩城 西单店");
+        assert_eq!(arcades[1].store_id.as_deref(), Some("1002"));
+        assert_eq!(arcades[2].store_id.as_deref(), Some("2001"));
+        // 机台数量可能是数字
+        assert_eq!(arcades[0].machine.as_deref(), Some("2"));
+        assert_eq!(arcades[2].machine.as_deref(), Some("4"));
+    }
+
+    #[test]
+    fn test_parse_store_json_missing_name() {
+        assert!(parse_store_json(r#"[{"id": "1", "address": "某地"}]"#).is_err());
+        // 不再读取其他键名
+        assert!(parse_store_json(r#"[{"id": "1", "name": "某厅", "address": "某地"}]"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_store_json_empty_id() {
+        let json =
+            r#"[{"id": "", "placeId": "3100000001", "arcadeName": "某厅", "address": "某地"}]"#;
+        assert_eq!(parse_store_json(json).unwrap()[0].store_id, None);
+    }
+
+    #[test]
+    fn test_plan_arcade_changes() {
+        let web_arcades = parse_all_store_list(STORE_LIST_HTML).unwrap();
+        let existing_arcades = existing(vec![
+            arcade(
+                1,
+                "环游嘉年华(王府井店)",
+                "北京市东城区王府井大街138号新东安广场5层",
+                false,
+            ),
+            arcade(2, "风云再起(徐家汇店)", "上海市徐汇区旧地址", false),
+            arcade(3, "已经关门的机厅", "某地", false),
+            arcade(4, "早就关门的机厅", "某地", true),
+        ]);
+
+        let changes = plan_arcade_changes(
+            &existing_arcades,
+            &web_arcades,
+            &RenameMatcher::new(0.75, 0.5),
+            1,
+        );
+
+        let new_names: Vec<_> = changes
+            .new_arcades
+            .iter()
+            .map(|arcade| arcade.name.as_str())
+            .collect();
+        assert_eq!(
+            new_names,
+            vec!["SEGA 电玩城 西单店", "大玩家超乐场(来福士店)"]
+        );
+
+        // 机厅1没有变化，机厅2地址变化
+        let mut updated: Vec<_> = changes
+            .updated_arcades
+            .iter()
+            .map(|(existing, web)| (existing.arcade_id, web.address.as_str()))
+            .collect();
+        updated.sort();
+        assert_eq!(updated, vec![(2, "上海市徐汇区虹桥路1号港汇恒隆广场6层")]);
+
+        let closing_ids: Vec<_> = changes
+            .closing_arcades
+            .iter()
+            .map(|arcade| arcade.arcade_id)
+            .collect();
+        assert_eq!(closing_ids, vec![3]);
+    }
+
+    #[test]
+    fn test_plan_arcade_changes_by_store_id() {
+        // 店铺ID只由机厅列表接口提供
+        let web_arcades = parse_store_json(STORE_LIST_JSON).unwrap();
+        let mut renamed = arcade(
+            7,
+            "风云再起徐家汇港汇店",
+            "上海市徐汇区虹桥路1号港汇恒隆广场6层",
+            false,
+        );
+        renamed.arcade_store_id = Some("2001".to_string());
+        let existing_arcades = existing(vec![renamed]);
+
+        let changes = plan_arcade_changes(
+            &existing_arcades,
+            &web_arcades,
+            &RenameMatcher::new(0.75, 0.5),
+            1,
+        );
+
+        // 店铺ID相同，视为改名而不是关闭旧机厅、新增新机厅
+        assert!(changes.closing_arcades.is_empty());
+        assert!(
+            changes
+                .new_arcades
+                .iter()
+                .all(|arcade| arcade.name != "风云再起(徐家汇店)")
+        );
+        assert_eq!(changes.updated_arcades.len(), 1);
+        assert_eq!(changes.updated_arcades[0].0.arcade_id, 7);
+        assert_eq!(changes.updated_arcades[0].1.name, "风云再起(徐家汇店)");
+    }
+
+    #[test]
+    fn test_plan_arcade_changes_reopened() {
+        let web_arcades = parse_all_store_list(STORE_LIST_HTML).unwrap();
+        let mut missing = arcade(
+            1,
+            "环游嘉年华(王府井店)",
+            "北京市东城区王府井大街138号新东安广场5层",
+            false,
+        );
+        missing.arcade_missing_count = 1;
+        let existing_arcades = existing(vec![
+            missing,
+            arcade(2, "风云再起(徐家汇店)", "上海市徐汇区旧地址", true),
+        ]);
+
+        let changes = plan_arcade_changes(
+            &existing_arcades,
+            &web_arcades,
+            &RenameMatcher::new(0.75, 0.5),
+            1,
+        );
+
+        // 已关闭的机厅重新出现在网站上，即使地址未变也会恢复
+        let reopened_ids: Vec<_> = changes
+            .reopened_arcades
+            .iter()
+            .map(|(existing, _)| existing.arcade_id)
+            .collect();
+        assert_eq!(reopened_ids, vec![2]);
+
+        // 之前缺失过的机厅重新出现，需要清零缺失计数
+        assert_eq!(changes.updated_arcades.len(), 1);
+        assert_eq!(changes.updated_arcades[0].0.arcade_id, 1);
+    }
+
+    #[test]
+    fn test_plan_arcade_changes_closure_grace() {
+        let web_arcades = parse_all_store_list(STORE_LIST_HTML).unwrap();
+        let mut almost_closed = arcade(4, "缺失两次的机厅", "某地", false);
+        almost_closed.arcade_missing_count = 2;
+        let existing_arcades = existing(vec![
+            arcade(3, "第一次缺失的机厅", "某地", false),
+            almost_closed,
+        ]);
+
+        let changes = plan_arcade_changes(
+            &existing_arcades,
+            &web_arcades,
+            &RenameMatcher::new(0.75, 0.5),
+            3,
+        );
+
+        let closing_ids: Vec<_> = changes
+            .closing_arcades
+            .iter()
+            .map(|arcade| arcade.arcade_id)
+            .collect();
+        assert_eq!(closing_ids, vec![4]);
+        let missing_ids: Vec<_> = changes
+            .missing_arcades
+            .iter()
+            .map(|arcade| arcade.arcade_id)
+            .collect();
+        assert_eq!(missing_ids, vec![3]);
+    }
+}
@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use maimap_scrape::store_source::{
+        FallbackStoreSource, HttpStoreSource, PageFormat, SnapshotStoreSource, StoreListPage,
+        StoreSource,
+    };
+    use maimap_utils::errors::Result;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const STORE_LIST_JSON: &str = include_str!("fixtures/store_list.json");
+
+    /// 启动只返回一次固定响应的本地 HTTP 服务，返回其地址
+    async fn serve_once(status: &'static str, body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf).await.unwrap();
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        });
+        format!("http://{}/maidx/rest/location", addr)
+    }
+
+    struct FixedSource;
+
+    #[async_trait]
+    impl StoreSource for FixedSource {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        async fn fetch(&self) -> Result<StoreListPage> {
+            StoreListPage::parse(STORE_LIST_JSON.to_string(), PageFormat::Json)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_http_store_source() {
+        let url = serve_once("200 OK", STORE_LIST_JSON).await;
+        let page = HttpStoreSource::new(url, 1).unwrap().fetch().await.unwrap();
+
+        assert_eq!(page.format, PageFormat::Json);
+        assert_eq!(page.raw, STORE_LIST_JSON);
+        assert_eq!(page.arcades.len(), 3);
+        assert_eq!(page.arcades[0].name, "环游嘉年华(王府井店)");
+        assert_eq!(
+            page.arcades[0].address,
+            "北京市东城区王府井大街138号新东安广场5层"
+        );
+        assert_eq!(page.arcades[1].name, "SEGA 电玩城 西单店");
+    }
+
+    #[tokio::test]
+    async fn test_http_store_source_too_few_arcades() {
+        let url = serve_once("200 OK", STORE_LIST_JSON).await;
+        assert!(
+            HttpStoreSource::new(url, 2000)
+                .unwrap()
+                .fetch()
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_store_source_server_error() {
+        let url = serve_once("503 Service Unavailable", "").await;
+        assert!(HttpStoreSource::new(url, 1).unwrap().fetch().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fallback_store_source() {
+        let url = serve_once("503 Service Unavailable", "").await;
+        let source = FallbackStoreSource::new(vec![
+            Box::new(HttpStoreSource::new(url, 1).unwrap()),
+            Box::new(FixedSource),
+        ]);
+
+        let page = source.fetch().await.unwrap();
+        assert_eq!(page.arcades.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_store_source() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+        let page = SnapshotStoreSource::new(format!("{}/store_list.json", dir))
+            .fetch()
+            .await
+            .unwrap();
+        assert_eq!(page.format, PageFormat::Json);
+        assert_eq!(page.arcades.len(), 3);
+
+        let page = SnapshotStoreSource::new(format!("{}/store_list.html", dir))
+            .fetch()
+            .await
+            .unwrap();
+        assert_eq!(page.format, PageFormat::Html);
+        assert_eq!(page.arcades.len(), 5);
+    }
+}
@@ -0,0 +1,23 @@
+use crate::res::ApiResponse;
+use maimap_utils::env::admin_token;
+use salvo::http::header::AUTHORIZATION;
+use salvo::prelude::*;
+
+/// 校验 `Authorization: Bearer <ADMIN_TOKEN>`，未配置 ADMIN_TOKEN 时拒绝所有请求
+#[handler]
+pub async fn admin_auth(req: &mut Request, res: &mut Response, ctrl: &mut FlowCtrl) {
+    let token = admin_token();
+    let authorized = !token.is_empty()
+        && req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|value| value == token);
+
+    if !authorized {
+        res.status_code(StatusCode::UNAUTHORIZED);
+        res.render(Json(ApiResponse::<()>::error("未授权的访问")));
+        ctrl.skip_rest();
+    }
+}
@@ -0,0 +1,21 @@
+use crate::handler::common::handle_error;
+use crate::res::ApiResponse;
+use maimap_utils::db::get_all_job_status;
+use maimap_utils::errors::Result;
+use maimap_utils::traits::ToResponse;
+use maimap_utils::types::JobStatusResponse;
+use salvo::prelude::*;
+
+#[handler]
+pub async fn get_jobs_handler(res: &mut Response) {
+    match get_jobs().await {
+        Ok((jobs, count)) => res.render(Json(ApiResponse::success(jobs).with_count(count))),
+        Err(e) => handle_error(res, e),
+    }
+}
+
+async fn get_jobs() -> Result<(Vec<JobStatusResponse>, usize)> {
+    let jobs = get_all_job_status().await?;
+    let count = jobs.len();
+    Ok((jobs.to_typed_response(), count))
+}
@@ -0,0 +1,47 @@
+use crate::handler::common::{handle_error, page_window};
+use crate::res::ApiResponse;
+use maimap_utils::db::{Collection, doc, get_mongodb_client};
+use maimap_utils::env::DB_NAME;
+use maimap_utils::errors::Result;
+use maimap_utils::traits::ToResponse;
+use maimap_utils::types::{ScrapeRun, ScrapeRunResponse};
+use salvo::prelude::*;
+use serde::Deserialize;
+
+#[handler]
+pub async fn get_scrape_runs_handler(req: &mut Request, res: &mut Response) {
+    match get_scrape_runs(req).await {
+        Ok((runs, count)) => res.render(Json(ApiResponse::success(runs).with_count(count))),
+        Err(e) => handle_error(res, e),
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct ScrapeRunsQuery {
+    page_index: Option<u32>,
+    page_size: Option<u32>,
+}
+
+async fn get_scrape_runs(req: &mut Request) -> Result<(Vec<ScrapeRunResponse>, usize)> {
+    let query: ScrapeRunsQuery = req.parse_queries::<ScrapeRunsQuery>()?;
+
+    let client = get_mongodb_client();
+    let coll_runs: Collection<ScrapeRun> = client.database(DB_NAME).collection("scrape_runs");
+
+    let count = coll_runs.count_documents(doc! {}).await?;
+
+    // 最近的运行记录排在前面，分页交给数据库完成
+    let mut find = coll_runs.find(doc! {}).sort(doc! {"started_at": -1});
+    if let Some((skip, limit)) = page_window(query.page_index, query.page_size)? {
+        find = find.skip(skip).limit(limit as i64);
+    }
+    let mut cursor = find.await?;
+
+    let mut runs = Vec::new();
+    while cursor.advance().await? {
+        let run = cursor.deserialize_current()?;
+        runs.push(run.to_typed_response());
+    }
+
+    Ok((runs, count as usize))
+}
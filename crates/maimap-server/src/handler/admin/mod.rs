@@ -0,0 +1,7 @@
+mod auth;
+mod get_jobs;
+mod get_scrape_runs;
+
+pub use auth::admin_auth;
+pub use get_jobs::get_jobs_handler;
+pub use get_scrape_runs::get_scrape_runs_handler;
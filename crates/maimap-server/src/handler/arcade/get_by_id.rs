@@ -7,7 +7,7 @@ use maimap_utils::env::DB_NAME;
 use maimap_utils::errors::AppError;
 use maimap_utils::errors::Result;
 use maimap_utils::traits::ToResponse;
-use maimap_utils::types::Arcade;
+use maimap_utils::types::{Arcade, ArcadeResponse};
 use salvo::prelude::*;
 
 use crate::handler::common::handle_error;
@@ -15,12 +15,14 @@ use crate::handler::common::handle_error;
 #[handler]
 pub async fn get_arcade_by_id_handler(req: &mut Request, res: &mut Response) {
     match get_arcade_by_id(req).await {
-        Ok(arcade) => res.render(Json(ApiResponse::success(arcade))),
+        Ok(Some(arcade)) => res.render(Json(ApiResponse::success(arcade))),
+        // 未找到时沿用原有的空对象
+        Ok(None) => res.render(Json(ApiResponse::success(serde_json::json!({})))),
         Err(e) => handle_error(res, e),
     }
 }
 
-async fn get_arcade_by_id(req: &mut Request) -> Result<serde_json::Value> {
+async fn get_arcade_by_id(req: &mut Request) -> Result<Option<ArcadeResponse>> {
     let arcade_id = req
         .param::<i32>("arcade_id")
         .ok_or_else(|| AppError::Validation("缺少arcade_id参数".to_string()))?;
@@ -31,8 +33,5 @@ async fn get_arcade_by_id(req: &mut Request) -> Result<serde_json::Value> {
         .find_one(doc! {"arcade_id": Int32(arcade_id)})
         .await?;
 
-    Ok(match result {
-        Some(arcade) => arcade.to_response(),
-        None => serde_json::json!({}),
-    })
+    Ok(result.map(|arcade| arcade.to_typed_response()))
 }
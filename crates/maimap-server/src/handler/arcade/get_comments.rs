@@ -7,7 +7,7 @@ use maimap_utils::env::DB_NAME;
 use maimap_utils::errors::AppError;
 use maimap_utils::errors::Result;
 use maimap_utils::traits::ToResponse;
-use maimap_utils::types::Comment;
+use maimap_utils::types::{Comment, CommentResponse};
 use salvo::prelude::Json;
 use salvo::{Request, Response, handler};
 
@@ -19,7 +19,7 @@ pub async fn get_comments_handler(req: &mut Request, res: &mut Response) {
     }
 }
 
-async fn get_comment(req: &mut Request) -> Result<(Vec<serde_json::Value>, usize)> {
+async fn get_comment(req: &mut Request) -> Result<(Vec<CommentResponse>, usize)> {
     let arcade_id = req
         .param::<i32>("arcade_id")
         .ok_or_else(|| AppError::Validation("缺少arcade_id参数".to_string()))?;
@@ -33,7 +33,7 @@ async fn get_comment(req: &mut Request) -> Result<(Vec<serde_json::Value>, usize
     let mut comments = Vec::new();
     while cursor.advance().await? {
         let doc = cursor.deserialize_current()?;
-        comments.push(doc.to_response());
+        comments.push(doc.to_typed_response());
     }
     let count = comments.len();
     Ok((comments, count))
@@ -7,7 +7,7 @@ use maimap_utils::env::DB_NAME;
 use maimap_utils::errors::AppError;
 use maimap_utils::errors::Result;
 use maimap_utils::traits::ToResponse;
-use maimap_utils::types::Tag;
+use maimap_utils::types::{Tag, TagResponse};
 use salvo::prelude::Json;
 use salvo::{Request, Response, handler};
 
@@ -19,7 +19,7 @@ pub async fn get_tags_handler(req: &mut Request, res: &mut Response) {
     }
 }
 
-async fn get_tag(req: &mut Request) -> Result<(Vec<serde_json::Value>, usize)> {
+async fn get_tag(req: &mut Request) -> Result<(Vec<TagResponse>, usize)> {
     let arcade_id = req
         .param::<i32>("arcade_id")
         .ok_or_else(|| AppError::Validation("缺少arcade_id参数".to_string()))?;
@@ -33,7 +33,7 @@ async fn get_tag(req: &mut Request) -> Result<(Vec<serde_json::Value>, usize)> {
     let mut tags = Vec::new();
     while cursor.advance().await? {
         let doc = cursor.deserialize_current()?;
-        tags.push(doc.to_response());
+        tags.push(doc.to_typed_response());
     }
     let count = tags.len();
     Ok((tags, count))
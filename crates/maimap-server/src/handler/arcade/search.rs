@@ -1,10 +1,11 @@
 use crate::handler::common::{handle_error, paginate_results};
 use crate::res::ApiResponse;
-use maimap_utils::db::{Collation, Collection, Document, doc, get_mongodb_client, to_bson};
+use maimap_utils::db::{Collation, Collection, Document, doc, get_mongodb_client};
 use maimap_utils::env::DB_NAME;
 use maimap_utils::errors::AppError;
 use maimap_utils::errors::Result;
-use maimap_utils::types::Arcade;
+use maimap_utils::traits::ToResponse;
+use maimap_utils::types::{Arcade, ArcadeSearchResult, ArcadeSearchResultResponse};
 use salvo::prelude::*;
 use serde::Deserialize;
 
@@ -27,7 +28,7 @@ struct SearchQuery {
     sort: Option<String>,
 }
 
-async fn search_arcade(req: &mut Request) -> Result<(Vec<serde_json::Value>, usize)> {
+async fn search_arcade(req: &mut Request) -> Result<(Vec<ArcadeSearchResultResponse>, usize)> {
     // 从请求中提取查询参数
     let query: SearchQuery = req.parse_queries::<SearchQuery>()?;
 
@@ -54,30 +55,24 @@ async fn search_arcade(req: &mut Request) -> Result<(Vec<serde_json::Value>, usi
     };
     pipeline.push(sort_doc);
 
-    // 修改字段以符合API要求
-    serialize_data(&mut pipeline);
-
     // 执行查询
     let client = get_mongodb_client();
     let coll_arcades: Collection<Arcade> = client.database(DB_NAME).collection("arcades");
     let mut cursor = coll_arcades
         .aggregate(pipeline)
         .collation(collation)
+        .with_type::<ArcadeSearchResult>()
         .await?;
 
-    // 收集结果
+    // 收集结果，与其他接口一样通过 ToResponse 输出
     let mut results = Vec::new();
     while cursor.advance().await? {
-        let doc = cursor.deserialize_current()?;
-        let json_value: serde_json::Value = to_bson(&doc)
-            .map_err(|e| AppError::Serialize(e.to_string()))?
-            .into();
-        results.push(json_value);
+        results.push(cursor.deserialize_current()?);
     }
     let total_count = results.len();
     let paged_results = paginate_results(&results, query.page_index, query.page_size)?;
 
-    Ok((paged_results, total_count))
+    Ok((paged_results.to_typed_response(), total_count))
 }
 
 fn generate_geo_doc(query: &SearchQuery) -> Result<Option<Document>> {
@@ -96,31 +91,11 @@ fn generate_geo_doc(query: &SearchQuery) -> Result<Option<Document>> {
     } else if query.lat.is_some() != query.lng.is_some()
         || query.lat.is_some() != query.range.is_some()
     {
-        return Err(AppError::Validation(
-            "地理位置搜索需要同时提供lat、lng和range三个参数".to_string(),
+        Err(
+            AppError::Validation("地理位置搜索需要同时提供lat、lng和range三个参数".to_string())
+                .into(),
         )
-        .into());
     } else {
         Ok(None)
     }
 }
-
-fn serialize_data(pipeline: &mut Vec<Document>) {
-    pipeline.push(doc! {
-        "$project": {
-            "_id": 0,
-            "arcade_pos": 0
-        },
-    });
-
-    pipeline.push(doc! {
-        "$addFields": {
-            "arcade_lat": { "$toDouble": "$arcade_lat" },
-            "arcade_lng": { "$toDouble": "$arcade_lng" },
-            "created_at": { "$dateToString": {
-                "format": "%Y-%m-%dT%H:%M:%S.%LZ",
-                "date": "$created_at"
-            }}
-        },
-    });
-}
@@ -14,11 +14,8 @@ pub fn handle_error(res: &mut Response, err: Error) {
     res.render(Json(ApiResponse::<()>::error(err.to_string())));
 }
 
-pub fn paginate_results<T: Clone>(
-    results: &[T],
-    page_index: Option<u32>,
-    page_size: Option<u32>,
-) -> Result<Vec<T>> {
+/// 校验分页参数，返回需要跳过的条数和每页大小；未分页时返回 `None`
+pub fn page_window(page_index: Option<u32>, page_size: Option<u32>) -> Result<Option<(u64, u32)>> {
     if page_index.is_some() != page_size.is_some() {
         return Err(AppError::Validation(
             "分页需要同时提供page_index、page_size两个参数".to_string(),
@@ -30,7 +27,22 @@ pub fn paginate_results<T: Clone>(
         if page_index < 1 || page_size < 1 {
             return Err(AppError::Validation("页码和每页大小必须大于0".to_string()).into());
         }
-        let start = ((page_index - 1) * page_size) as usize;
+        Ok(Some((
+            (page_index as u64 - 1) * page_size as u64,
+            page_size,
+        )))
+    } else {
+        Ok(None)
+    }
+}
+
+pub fn paginate_results<T: Clone>(
+    results: &[T],
+    page_index: Option<u32>,
+    page_size: Option<u32>,
+) -> Result<Vec<T>> {
+    if let Some((skip, page_size)) = page_window(page_index, page_size)? {
+        let start = skip as usize;
         let end = std::cmp::min(start + page_size as usize, results.len());
 
         if start < results.len() {
@@ -1,7 +1,8 @@
 use maimap_server::router::router;
-use maimap_utils::db::ensure_mongodb_connected;
-use maimap_utils::env::check_required_env_vars;
-use salvo::cors::{Cors, AllowHeaders, AllowOrigin, AllowMethods};
+use maimap_utils::db::{ensure_mongodb_connected, get_mongodb_client};
+use maimap_utils::env::{DB_NAME, check_required_env_vars};
+use maimap_utils::indexes::{ensure_indexes, index_specs};
+use salvo::cors::{AllowHeaders, AllowMethods, AllowOrigin, Cors};
 use salvo::prelude::*;
 
 #[tokio::main]
@@ -10,6 +11,22 @@ async fn main() {
     ensure_mongodb_connected().await;
     tracing_subscriber::fmt().init();
 
+    // 唯一索引缺失时无法保证机厅ID不重复，拒绝启动
+    let db = get_mongodb_client().database(DB_NAME);
+    let specs = index_specs();
+    match ensure_indexes(&db, &specs).await {
+        Ok(drifts) => {
+            if let Some(drift) = drifts.iter().find(|drift| drift.breaks_uniqueness(&specs)) {
+                tracing::error!("唯一索引{}，请清理重复数据后重新启动", drift);
+                std::process::exit(1);
+            }
+        }
+        Err(e) => {
+            tracing::error!("检查数据库索引失败：{:#}", e);
+            std::process::exit(1);
+        }
+    }
+
     let router = router();
     let cors = Cors::new()
         .allow_origin(AllowOrigin::any())
@@ -1,7 +1,8 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Serialize)]
-pub struct ApiResponse<T: Serialize> {
+/// 接口的统一响应，测试中也用它反序列化响应
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ApiResponse<T> {
     pub success: bool,
     pub data: Option<T>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -1,7 +1,12 @@
+use crate::handler::admin::{admin_auth, get_jobs_handler, get_scrape_runs_handler};
 use crate::handler::arcade::{get_arcade_by_id_handler, search_arcades_handler};
 use salvo::Router;
 
 pub fn router() -> Router {
+    Router::new().push(arcade_router()).push(admin_router())
+}
+
+fn arcade_router() -> Router {
     Router::with_path("arcades")
         .get(search_arcades_handler)
         .push(
@@ -13,3 +18,10 @@ pub fn router() -> Router {
                 .push(Router::with_path("tags").get(crate::handler::arcade::get_tags_handler)),
         )
 }
+
+fn admin_router() -> Router {
+    Router::with_path("admin")
+        .hoop(admin_auth)
+        .push(Router::with_path("scrape-runs").get(get_scrape_runs_handler))
+        .push(Router::with_path("jobs").get(get_jobs_handler))
+}
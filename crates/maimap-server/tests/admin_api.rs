@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod tests {
+    use maimap_server::res::ApiResponse;
+    use maimap_server::router::router;
+    use maimap_utils::db::{
+        DateTime, doc, ensure_test_mongodb_connected, get_mongodb_client, insert_scrape_run,
+    };
+    use maimap_utils::env::{DB_NAME, admin_token};
+    use maimap_utils::types::{ScrapeRun, ScrapeRunResponse};
+    use salvo::prelude::*;
+    use salvo::test::{ResponseExt, TestClient};
+
+    #[tokio::test]
+    async fn test_scrape_runs_requires_token() {
+        let service = Service::new(router());
+        let mut res = TestClient::get("http://127.0.0.1:5800/admin/scrape-runs")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::UNAUTHORIZED));
+        let content: serde_json::Value = res.take_json().await.expect("解析JSON失败");
+        assert_eq!(content["success"], false);
+
+        let res = TestClient::get("http://127.0.0.1:5800/admin/scrape-runs")
+            .add_header("Authorization", "Bearer invalid-token", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::UNAUTHORIZED));
+    }
+
+    #[tokio::test]
+    async fn test_jobs_requires_token() {
+        let service = Service::new(router());
+        let res = TestClient::get("http://127.0.0.1:5800/admin/jobs")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::UNAUTHORIZED));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URI and ADMIN_TOKEN"]
+    async fn test_scrape_runs_pagination() {
+        ensure_test_mongodb_connected().await;
+        let token = admin_token();
+        assert!(!token.is_empty(), "需要设置 ADMIN_TOKEN");
+
+        // 开始时间放在未来，保证这几条记录排在最前面
+        let base = DateTime::now().timestamp_millis() + 1_000_000_000;
+        let mut runs = Vec::new();
+        for offset in 0..3 {
+            let mut run = ScrapeRun::start();
+            run.started_at = DateTime::from_millis(base + offset * 1000);
+            insert_scrape_run(&run).await.expect("写入爬取记录失败");
+            runs.push(run);
+        }
+
+        let service = Service::new(router());
+        let first: ApiResponse<Vec<ScrapeRunResponse>> =
+            TestClient::get("http://127.0.0.1:5800/admin/scrape-runs?page_index=1&page_size=2")
+                .add_header("Authorization", format!("Bearer {}", token), true)
+                .send(&service)
+                .await
+                .take_json()
+                .await
+                .expect("解析JSON失败");
+        let second: ApiResponse<Vec<ScrapeRunResponse>> =
+            TestClient::get("http://127.0.0.1:5800/admin/scrape-runs?page_index=2&page_size=2")
+                .add_header("Authorization", format!("Bearer {}", token), true)
+                .send(&service)
+                .await
+                .take_json()
+                .await
+                .expect("解析JSON失败");
+
+        let ids: Vec<_> = runs.iter().map(|run| run.id).collect();
+        get_mongodb_client()
+            .database(DB_NAME)
+            .collection::<ScrapeRun>("scrape_runs")
+            .delete_many(doc! {"_id": {"$in": &ids}})
+            .await
+            .expect("清理爬取记录失败");
+
+        assert!(first.success);
+        assert!(first.count.unwrap() >= 3);
+        let first: Vec<_> = first.data.unwrap().into_iter().map(|run| run.id).collect();
+        assert_eq!(first, vec![ids[2].to_hex(), ids[1].to_hex()]);
+        let second = second.data.unwrap();
+        assert_eq!(second[0].id, ids[0].to_hex());
+    }
+}
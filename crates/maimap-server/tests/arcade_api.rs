@@ -1,11 +1,12 @@
-mod types;
-
 #[cfg(test)]
 mod tests {
-    use crate::types::{ApiResponse, Arcade, Comment, Tag};
+    use maimap_server::res::ApiResponse;
     use maimap_server::router::router;
     use maimap_utils::db::ensure_test_mongodb_connected;
     use maimap_utils::env::check_required_env_vars;
+    use maimap_utils::types::{
+        ArcadeResponse, ArcadeSearchResultResponse, CommentResponse, TagResponse,
+    };
     use salvo::prelude::*;
     use salvo::test::{ResponseExt, TestClient};
     use std::time::Duration;
@@ -15,14 +16,14 @@ mod tests {
         check_required_env_vars();
         ensure_test_mongodb_connected().await;
         let service = Service::new(router());
-        let content: ApiResponse<Arcade> =
-            TestClient::get("http://127.0.0.1:5800/arcades/1514".to_string())
+        let content: ApiResponse<ArcadeResponse> =
+            TestClient::get("http://127.0.0.1:5800/arcades/1514")
                 .send(&service)
                 .await
                 .take_json()
                 .await
                 .expect("解析JSON失败");
-        assert_eq!(content.success, true);
+        assert!(content.success);
         assert_eq!(content.data.unwrap().arcade_id, 1514);
         tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
     }
@@ -32,14 +33,22 @@ mod tests {
         check_required_env_vars();
         ensure_test_mongodb_connected().await;
         let service = Service::new(router());
-        let content: ApiResponse<Vec<Arcade>> =
-            TestClient::get("http://127.0.0.1:5800/arcades?name=环游嘉年华&lat=39.909333&lng=116.397183&range=1000000&sort=Distance&page_index=1&page_size=20".to_string())
+        let content: ApiResponse<Vec<ArcadeSearchResultResponse>> =
+            TestClient::get("http://127.0.0.1:5800/arcades?name=环游嘉年华&lat=39.909333&lng=116.397183&range=1000000&sort=Distance&page_index=1&page_size=20")
                 .send(&service)
                 .await
                 .take_json()
                 .await
                 .expect("解析JSON失败");
         content.count.unwrap();
+        // 地理位置搜索的结果带有距离
+        assert!(
+            content
+                .data
+                .unwrap()
+                .iter()
+                .all(|result| result.distance.is_some())
+        );
         tokio::time::sleep(Duration::from_millis(1000)).await;
     }
 
@@ -48,15 +57,15 @@ mod tests {
         check_required_env_vars();
         ensure_test_mongodb_connected().await;
         let service = Service::new(router());
-        let content: ApiResponse<Vec<Comment>> =
-            TestClient::get("http://127.0.0.1:5800/arcades/1514/comments".to_string())
+        let content: ApiResponse<Vec<CommentResponse>> =
+            TestClient::get("http://127.0.0.1:5800/arcades/1514/comments")
                 .send(&service)
                 .await
                 .take_json()
                 .await
                 .expect("解析JSON失败");
         content.count.unwrap();
-        assert_eq!(content.success, true);
+        assert!(content.success);
         tokio::time::sleep(Duration::from_millis(1000)).await;
     }
 
@@ -65,15 +74,15 @@ mod tests {
         check_required_env_vars();
         ensure_test_mongodb_connected().await;
         let service = Service::new(router());
-        let content: ApiResponse<Vec<Tag>> =
-            TestClient::get("http://127.0.0.1:5800/arcades/1155/tags".to_string())
+        let content: ApiResponse<Vec<TagResponse>> =
+            TestClient::get("http://127.0.0.1:5800/arcades/1155/tags")
                 .send(&service)
                 .await
                 .take_json()
                 .await
                 .expect("解析JSON失败");
         content.count.unwrap();
-        assert_eq!(content.success, true);
+        assert!(content.success);
         tokio::time::sleep(Duration::from_millis(1000)).await;
     }
 }
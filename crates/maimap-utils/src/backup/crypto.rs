@@ -0,0 +1,291 @@
+use crate::env::{backup_encryption_key_id, backup_encryption_keys};
+use crate::errors::{AppError, Context, Result};
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::stream::{DecryptorBE32, EncryptorBE32};
+use aes_gcm::aead::{Aead, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// 加密后的备份文件名后缀
+pub const ENCRYPTED_SUFFIX: &str = ".enc";
+/// 清单中记录的加密算法
+pub const ENCRYPTION_ALGORITHM: &str = "AES-256-GCM-STREAM";
+/// 分块加密的文件开头的标识
+const MAGIC: &[u8; 8] = b"MAIMAPE2";
+/// STREAM 构造中随机数的长度，其余 5 字节为块序号与最后一块的标记
+const STREAM_NONCE_LENGTH: usize = 7;
+/// 每块明文的大小
+const CHUNK_SIZE: usize = 64 * 1024;
+/// 每块密文末尾的认证标签长度
+const TAG_LENGTH: usize = 16;
+/// 整个文件一次加密的旧格式，只用于解密此前的备份
+const LEGACY_MAGIC: &[u8; 8] = b"MAIMAPE1";
+const LEGACY_NONCE_LENGTH: usize = 12;
+
+/// 清单中记录的加密信息
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct EncryptionInfo {
+    /// 加密算法
+    pub algorithm: String,
+    /// 加密所用密钥的 ID，恢复时据此选择密钥
+    pub key_id: String,
+}
+
+/// 备份加密密钥
+pub struct BackupKey {
+    id: String,
+    key: Key<Aes256Gcm>,
+}
+
+impl BackupKey {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// 全部可用的备份密钥。
+///
+/// 新备份使用 `current` 指定的密钥加密；轮换密钥时保留旧密钥，以便解密此前的备份。
+pub struct BackupKeyring {
+    keys: Vec<BackupKey>,
+    current: Option<String>,
+}
+
+impl BackupKeyring {
+    /// `keys` 形如 `id1:<64位十六进制>,id2:<64位十六进制>`，`current` 为空时不加密新备份
+    pub fn parse(keys: &str, current: &str) -> Result<Self> {
+        let mut parsed = Vec::new();
+        for entry in keys.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (id, hex_key) = entry.split_once(':').ok_or_else(|| {
+                AppError::Configuration(format!("备份密钥 '{}' 应为 <ID>:<密钥> 的形式", entry))
+            })?;
+            let bytes = hex::decode(hex_key.trim())
+                .ok()
+                .filter(|bytes| bytes.len() == 32)
+                .ok_or_else(|| {
+                    AppError::Configuration(format!(
+                        "备份密钥 {} 应为 64 位十六进制（32 字节）",
+                        id
+                    ))
+                })?;
+            parsed.push(BackupKey {
+                id: id.trim().to_string(),
+                key: *Key::<Aes256Gcm>::from_slice(&bytes),
+            });
+        }
+
+        let current = current.trim();
+        let keyring = Self {
+            keys: parsed,
+            current: (!current.is_empty()).then(|| current.to_string()),
+        };
+        if let Some(id) = &keyring.current {
+            keyring.get(id)?;
+        }
+        Ok(keyring)
+    }
+
+    pub fn from_env() -> Result<Self> {
+        Self::parse(&backup_encryption_keys(), &backup_encryption_key_id())
+    }
+
+    /// 加密新备份所用的密钥，未启用加密时为 `None`
+    pub fn current(&self) -> Option<&BackupKey> {
+        self.current.as_deref().and_then(|id| self.get(id).ok())
+    }
+
+    fn get(&self, id: &str) -> Result<&BackupKey> {
+        self.keys.iter().find(|key| key.id == id).ok_or_else(|| {
+            AppError::Configuration(format!("BACKUP_ENCRYPTION_KEYS 中没有 ID 为 {} 的密钥", id))
+                .into()
+        })
+    }
+}
+
+/// 加密文件的头部：标识、密钥 ID 与随机数，同时作为附加认证数据
+fn header(magic: &[u8; 8], key_id: &[u8], nonce: &[u8]) -> Result<Vec<u8>> {
+    let id_length = u8::try_from(key_id.len()).map_err(|_| {
+        AppError::Configuration(format!(
+            "备份密钥 ID 过长：{}",
+            String::from_utf8_lossy(key_id)
+        ))
+    })?;
+    let mut header = Vec::with_capacity(magic.len() + 1 + key_id.len() + nonce.len());
+    header.extend_from_slice(magic);
+    header.push(id_length);
+    header.extend_from_slice(key_id);
+    header.extend_from_slice(nonce);
+    Ok(header)
+}
+
+/// 读取最多 `size` 字节，只有到达文件末尾时才会少于 `size`
+fn read_chunk(reader: &mut impl Read, size: usize) -> std::io::Result<Vec<u8>> {
+    let mut chunk = Vec::with_capacity(size);
+    reader.take(size as u64).read_to_end(&mut chunk)?;
+    Ok(chunk)
+}
+
+/// 用 AES-256-GCM 的 STREAM 构造分块加密 `source`，写入 `target`。
+///
+/// 每块独立认证，块序号与最后一块的标记参与随机数，块被删除、重排或截断时解密失败；
+/// 加密时只需在内存中保留两块数据。
+pub fn encrypt_file(key: &BackupKey, source: &Path, target: &Path) -> Result<()> {
+    let mut reader = BufReader::new(
+        File::open(source).with_context(|| format!("读取备份文件 {} 失败", source.display()))?,
+    );
+    let mut writer = BufWriter::new(
+        File::create(target).with_context(|| format!("写入加密备份 {} 失败", target.display()))?,
+    );
+
+    let mut nonce = [0u8; STREAM_NONCE_LENGTH];
+    OsRng.fill_bytes(&mut nonce);
+    let header = header(MAGIC, key.id.as_bytes(), &nonce)?;
+    writer.write_all(&header)?;
+
+    let failed = || AppError::BackupExecution("加密备份失败".to_string());
+    let mut encryptor = EncryptorBE32::from_aead(Aes256Gcm::new(&key.key), nonce.as_slice().into());
+    let mut chunk = read_chunk(&mut reader, CHUNK_SIZE)?;
+    loop {
+        // 读到下一块为空时，当前块即为最后一块
+        let next = read_chunk(&mut reader, CHUNK_SIZE)?;
+        let payload = Payload {
+            msg: &chunk,
+            aad: &header,
+        };
+        if next.is_empty() {
+            writer.write_all(&encryptor.encrypt_last(payload).map_err(|_| failed())?)?;
+            break;
+        }
+        writer.write_all(&encryptor.encrypt_next(payload).map_err(|_| failed())?)?;
+        chunk = next;
+    }
+
+    writer
+        .flush()
+        .with_context(|| format!("写入加密备份 {} 失败", target.display()))?;
+    Ok(())
+}
+
+/// 读取加密备份的密钥 ID，未加密的文件返回 `None`
+pub fn encrypted_key_id(path: &Path) -> Result<Option<String>> {
+    let mut file =
+        File::open(path).with_context(|| format!("打开备份文件 {} 失败", path.display()))?;
+    let mut prefix = [0u8; 9];
+    if file.read_exact(&mut prefix).is_err()
+        || (&prefix[..8] != MAGIC && &prefix[..8] != LEGACY_MAGIC)
+    {
+        return Ok(None);
+    }
+    let mut id = vec![0u8; prefix[8] as usize];
+    file.read_exact(&mut id).context("加密备份的头部不完整")?;
+    Ok(Some(String::from_utf8_lossy(&id).to_string()))
+}
+
+/// 用头部记录的密钥解密 `source`，写入 `target`，返回所用密钥的 ID。
+///
+/// 分块加密的备份边解密边写入，失败时删除已写入的部分；旧格式的备份整体读入后解密。
+pub fn decrypt_file(keyring: &BackupKeyring, source: &Path, target: &Path) -> Result<String> {
+    let mut reader = BufReader::new(
+        File::open(source).with_context(|| format!("读取备份文件 {} 失败", source.display()))?,
+    );
+    let invalid = || AppError::BackupExecution("加密备份的头部不完整".to_string());
+
+    let mut magic = [0u8; 8];
+    if reader.read_exact(&mut magic).is_err() || (&magic != MAGIC && &magic != LEGACY_MAGIC) {
+        return Err(AppError::BackupExecution("备份文件未加密".to_string()).into());
+    }
+    if &magic == LEGACY_MAGIC {
+        return decrypt_legacy_file(keyring, source, target);
+    }
+
+    let mut id_length = [0u8; 1];
+    reader.read_exact(&mut id_length).map_err(|_| invalid())?;
+    let mut key_id = vec![0u8; id_length[0] as usize];
+    reader.read_exact(&mut key_id).map_err(|_| invalid())?;
+    let mut nonce = [0u8; STREAM_NONCE_LENGTH];
+    reader.read_exact(&mut nonce).map_err(|_| invalid())?;
+
+    let header = header(MAGIC, &key_id, &nonce)?;
+    let key_id = String::from_utf8_lossy(&key_id).to_string();
+    let key = keyring.get(&key_id)?;
+
+    let result = decrypt_chunks(key, &header, &nonce, &mut reader, target);
+    if result.is_err() {
+        let _ = std::fs::remove_file(target);
+    }
+    result?;
+    Ok(key_id)
+}
+
+fn decrypt_chunks(
+    key: &BackupKey,
+    header: &[u8],
+    nonce: &[u8],
+    reader: &mut impl Read,
+    target: &Path,
+) -> Result<()> {
+    let mut writer = BufWriter::new(
+        File::create(target)
+            .with_context(|| format!("写入解密后的备份 {} 失败", target.display()))?,
+    );
+    let failed =
+        || AppError::BackupExecution(format!("解密备份失败，密钥 {} 不正确或文件已损坏", key.id));
+
+    let mut decryptor = DecryptorBE32::from_aead(Aes256Gcm::new(&key.key), nonce.into());
+    let mut chunk = read_chunk(reader, CHUNK_SIZE + TAG_LENGTH)?;
+    loop {
+        let next = read_chunk(reader, CHUNK_SIZE + TAG_LENGTH)?;
+        let payload = Payload {
+            msg: &chunk,
+            aad: header,
+        };
+        if next.is_empty() {
+            writer.write_all(&decryptor.decrypt_last(payload).map_err(|_| failed())?)?;
+            break;
+        }
+        writer.write_all(&decryptor.decrypt_next(payload).map_err(|_| failed())?)?;
+        chunk = next;
+    }
+
+    writer
+        .flush()
+        .with_context(|| format!("写入解密后的备份 {} 失败", target.display()))?;
+    Ok(())
+}
+
+/// 解密整个文件一次加密的旧格式备份
+fn decrypt_legacy_file(keyring: &BackupKeyring, source: &Path, target: &Path) -> Result<String> {
+    let content =
+        std::fs::read(source).with_context(|| format!("读取备份文件 {} 失败", source.display()))?;
+    let invalid = || AppError::BackupExecution("加密备份的头部不完整".to_string());
+
+    if content.len() < LEGACY_MAGIC.len() + 1 {
+        return Err(invalid().into());
+    }
+    let id_end = LEGACY_MAGIC.len() + 1 + content[LEGACY_MAGIC.len()] as usize;
+    let header_end = id_end + LEGACY_NONCE_LENGTH;
+    if content.len() < header_end {
+        return Err(invalid().into());
+    }
+    let key_id = String::from_utf8_lossy(&content[LEGACY_MAGIC.len() + 1..id_end]).to_string();
+    let key = keyring.get(&key_id)?;
+
+    let plaintext = Aes256Gcm::new(&key.key)
+        .decrypt(
+            Nonce::from_slice(&content[id_end..header_end]),
+            Payload {
+                msg: &content[header_end..],
+                aad: &content[..header_end],
+            },
+        )
+        .map_err(|_| {
+            AppError::BackupExecution(format!("解密备份失败，密钥 {} 不正确或文件已损坏", key_id))
+        })?;
+
+    std::fs::write(target, plaintext)
+        .with_context(|| format!("写入解密后的备份 {} 失败", target.display()))?;
+    Ok(key_id)
+}
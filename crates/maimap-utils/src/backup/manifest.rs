@@ -0,0 +1,184 @@
+use super::crypto::{BackupKeyring, EncryptionInfo, decrypt_file, encrypted_key_id};
+use super::native::{CollectionManifest, count_archive};
+use super::store::BackupStore;
+use super::{BackupFormat, decrypted_path};
+use crate::env::backup_path;
+use crate::errors::{AppError, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// 与备份文件放在一起的清单，用于校验备份是否完整
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct BackupManifest {
+    /// 备份文件名
+    pub archive: String,
+    /// 备份格式
+    pub format: BackupFormat,
+    /// 导出的数据库名
+    pub database: String,
+    /// 备份时间（RFC 3339）
+    pub created_at: String,
+    /// 备份文件大小（字节）
+    pub size: u64,
+    /// 备份文件的 SHA-256（十六进制）
+    pub sha256: String,
+    /// 各集合的文档数。mongodump 格式为导出后立即统计的数量，导出期间有写入时可能略有出入
+    pub collections: Vec<CollectionManifest>,
+    /// 加密信息，未加密的备份为空
+    #[serde(default)]
+    pub encryption: Option<EncryptionInfo>,
+}
+
+/// 备份文件 `key` 的清单文件名
+pub fn manifest_key(key: &str) -> String {
+    format!("{}.manifest.json", key)
+}
+
+/// 流式计算文件的 SHA-256 与大小
+pub fn sha256_file(path: &Path) -> Result<(String, u64)> {
+    let file = File::open(path).with_context(|| format!("打开备份文件 {} 失败", path.display()))?;
+    let mut reader = BufReader::new(file);
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    let mut size = 0;
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+    Ok((hex::encode(hasher.finalize()), size))
+}
+
+/// 读取本地的清单文件
+pub fn read_manifest(path: &Path) -> Result<BackupManifest> {
+    let content =
+        std::fs::read(path).with_context(|| format!("读取清单文件 {} 失败", path.display()))?;
+    serde_json::from_slice(&content).map_err(|e| AppError::Parse(e.to_string()).into())
+}
+
+/// 写入本地的清单文件
+pub fn write_manifest(path: &Path, manifest: &BackupManifest) -> Result<()> {
+    let content =
+        serde_json::to_vec_pretty(manifest).map_err(|e| AppError::Serialize(e.to_string()))?;
+    std::fs::write(path, content)
+        .with_context(|| format!("写入清单文件 {} 失败", path.display()))?;
+    Ok(())
+}
+
+/// 比对备份文件与清单，返回发现的问题，为空表示校验通过。
+///
+/// 总会比对大小、SHA-256 与加密所用的密钥；原生格式还会读取整个备份（加密的先用 `keyring` 解密），
+/// 比对各集合的文档数。
+pub fn verify_archive(
+    path: &Path,
+    manifest: &BackupManifest,
+    keyring: &BackupKeyring,
+) -> Result<Vec<String>> {
+    let mut problems = Vec::new();
+
+    let (sha256, size) = sha256_file(path)?;
+    if size != manifest.size {
+        problems.push(format!("文件大小为 {}，清单中为 {}", size, manifest.size));
+    }
+    if sha256 != manifest.sha256 {
+        problems.push(format!(
+            "SHA-256 为 {}，清单中为 {}",
+            sha256, manifest.sha256
+        ));
+    }
+
+    let key_id = encrypted_key_id(path)?;
+    let expected_key_id = manifest.encryption.as_ref().map(|info| &info.key_id);
+    if key_id.as_ref() != expected_key_id {
+        problems.push(format!(
+            "加密密钥为 {}，清单中为 {}",
+            key_id.as_deref().unwrap_or("无"),
+            expected_key_id.map(String::as_str).unwrap_or("无")
+        ));
+    }
+
+    if manifest.format == BackupFormat::Native {
+        let decrypted = decrypted_path(path);
+        let counted = match key_id {
+            Some(_) => {
+                decrypt_file(keyring, path, &decrypted).and_then(|_| count_archive(&decrypted))
+            }
+            None => count_archive(path),
+        };
+        let _ = std::fs::remove_file(&decrypted);
+        match counted {
+            Ok(counted) => {
+                for expected in &manifest.collections {
+                    let actual = counted
+                        .iter()
+                        .find(|collection| collection.name == expected.name)
+                        .map(|collection| collection.count);
+                    match actual {
+                        Some(count) if count == expected.count => {}
+                        Some(count) => problems.push(format!(
+                            "集合 {} 有 {} 个文档，清单中为 {}",
+                            expected.name, count, expected.count
+                        )),
+                        None => problems.push(format!("备份中缺少集合 {}", expected.name)),
+                    }
+                }
+            }
+            Err(e) => problems.push(format!("读取备份内容失败：{:#}", e)),
+        }
+    }
+
+    Ok(problems)
+}
+
+/// 校验本地备份文件，清单为同目录下的 `<文件名>.manifest.json`
+pub fn verify_local_backup(path: &Path) -> Result<BackupManifest> {
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| AppError::Validation(format!("无效的备份文件：{}", path.display())))?;
+    let manifest = read_manifest(&path.with_file_name(manifest_key(file_name)))?;
+    check(path, &manifest, &BackupKeyring::from_env()?)?;
+    Ok(manifest)
+}
+
+/// 从备份存储中重新下载 `key` 与其清单并校验
+pub async fn verify_stored_backup(store: &dyn BackupStore, key: &str) -> Result<BackupManifest> {
+    let dir = PathBuf::from(backup_path());
+    std::fs::create_dir_all(&dir).context("创建备份目录失败")?;
+    let archive = dir.join(format!("verify_{}", key));
+    let manifest_path = dir.join(format!("verify_{}", manifest_key(key)));
+
+    info!("从{}下载 {} 及其清单进行校验", store.describe(), key);
+    let result = async {
+        store.get(&manifest_key(key), &manifest_path).await?;
+        store.get(key, &archive).await?;
+        let manifest = read_manifest(&manifest_path)?;
+        check(&archive, &manifest, &BackupKeyring::from_env()?)?;
+        Ok(manifest)
+    }
+    .await;
+
+    let _ = std::fs::remove_file(&archive);
+    let _ = std::fs::remove_file(&manifest_path);
+    result
+}
+
+fn check(path: &Path, manifest: &BackupManifest, keyring: &BackupKeyring) -> Result<()> {
+    let problems = verify_archive(path, manifest, keyring)?;
+    if !problems.is_empty() {
+        return Err(AppError::BackupExecution(format!(
+            "备份 {} 校验失败：{}",
+            manifest.archive,
+            problems.join("；")
+        ))
+        .into());
+    }
+    Ok(())
+}
@@ -0,0 +1,369 @@
+pub mod crypto;
+pub mod manifest;
+pub mod native;
+pub mod retention;
+pub mod store;
+
+use crate::db::{Database, Document, doc, get_mongodb_client};
+use crate::env::{DB_NAME, backup_format, backup_path, database_uri};
+use crate::errors::AppError;
+use anyhow::{Context, Result};
+use crypto::{
+    BackupKeyring, ENCRYPTED_SUFFIX, ENCRYPTION_ALGORITHM, EncryptionInfo, decrypt_file,
+    encrypted_key_id,
+};
+use manifest::{BackupManifest, manifest_key, sha256_file, write_manifest};
+use native::CollectionManifest;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use store::{BackupObject, BackupStore};
+use tracing::{info, warn};
+
+/// 备份文件的格式
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupFormat {
+    /// 调用 mongodump 生成的归档，需要安装 MongoDB Database Tools
+    Mongodump,
+    /// 通过驱动导出的 gzip 压缩 BSON 归档，见 [`native`]
+    Native,
+}
+
+impl BackupFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            BackupFormat::Mongodump => "gz",
+            BackupFormat::Native => "bson.gz",
+        }
+    }
+}
+
+impl FromStr for BackupFormat {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "mongodump" => Ok(Self::Mongodump),
+            "native" => Ok(Self::Native),
+            other => Err(AppError::Configuration(format!(
+                "未知的备份格式 '{}'，可选 mongodump、native",
+                other
+            ))),
+        }
+    }
+}
+
+/// 按 `BACKUP_FORMAT` 导出数据库并保存到备份存储，返回备份文件名
+pub async fn backup_database() -> Result<String> {
+    let backup_dir = backup_path();
+    std::fs::create_dir_all(&backup_dir).context("创建备份目录失败")?;
+
+    // 先检查配置，避免导出后才发现无法上传
+    let format = backup_format().parse::<BackupFormat>()?;
+    let keyring = BackupKeyring::from_env()?;
+    let store = store::backup_store_from_env()?;
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| AppError::TimestampGeneration(e.to_string()))?
+        .as_secs();
+
+    let mut filename = format!("maimap_{}.{}", timestamp, format.extension());
+    let mut filepath = format!("{}{}", backup_dir, filename);
+    info!("备份文件：{}", filepath);
+    let db = get_mongodb_client().database(DB_NAME);
+    let collections = match format {
+        BackupFormat::Mongodump => {
+            mongodump(&filepath)?;
+            count_collections(&db).await?
+        }
+        BackupFormat::Native => {
+            native::export_native(&db, Path::new(&filepath))
+                .await?
+                .collections
+        }
+    };
+
+    // 上传前加密，只保留加密后的文件
+    let encryption = match keyring.current() {
+        Some(key) => {
+            let encrypted = format!("{}{}", filepath, ENCRYPTED_SUFFIX);
+            crypto::encrypt_file(key, Path::new(&filepath), Path::new(&encrypted))?;
+            std::fs::remove_file(&filepath).context("删除未加密的备份文件失败")?;
+            info!("已使用密钥 {} 加密备份", key.id());
+            filename.push_str(ENCRYPTED_SUFFIX);
+            filepath = encrypted;
+            Some(EncryptionInfo {
+                algorithm: ENCRYPTION_ALGORITHM.to_string(),
+                key_id: key.id().to_string(),
+            })
+        }
+        None => None,
+    };
+
+    // 在备份文件旁写入清单，供 verify 校验
+    let (sha256, size) = sha256_file(Path::new(&filepath))?;
+    let manifest = BackupManifest {
+        archive: filename.clone(),
+        format,
+        database: DB_NAME.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        size,
+        sha256,
+        collections,
+        encryption,
+    };
+    let manifest_path = format!("{}{}", backup_dir, manifest_key(&filename));
+    write_manifest(Path::new(&manifest_path), &manifest)?;
+    info!("备份文件 SHA-256：{}，大小 {} 字节", manifest.sha256, size);
+
+    info!("上传备份文件到{}", store.describe());
+    store.put(&filename, Path::new(&filepath)).await?;
+    store
+        .put(&manifest_key(&filename), Path::new(&manifest_path))
+        .await?;
+
+    Ok(filename)
+}
+
+/// 统计数据库中各集合的文档数
+async fn count_collections(db: &Database) -> Result<Vec<CollectionManifest>> {
+    let mut names = db.list_collection_names().await?;
+    names.retain(|name| !name.starts_with("system."));
+    names.sort();
+
+    let mut collections = Vec::new();
+    for name in names {
+        let count = db
+            .collection::<Document>(&name)
+            .count_documents(doc! {})
+            .await?;
+        collections.push(CollectionManifest { name, count });
+    }
+    Ok(collections)
+}
+
+fn mongodump(filepath: &str) -> Result<()> {
+    let output = Command::new("mongodump")
+        .arg(format!("--uri={}", database_uri()))
+        .arg(format!("--db={}", DB_NAME))
+        .arg("--gzip")
+        .arg(format!("--archive={}", filepath))
+        .output()
+        .context("执行备份命令失败，请确认已安装 mongodump 或设置 BACKUP_FORMAT=native")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        return Err(AppError::CommandExecution {
+            status: output.status,
+            stderr,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// 备份文件名的前缀与后缀，存储中的其他文件不视为备份
+const BACKUP_PREFIX: &str = "maimap_";
+const BACKUP_SUFFIX: &str = ".gz";
+
+fn is_backup_key(key: &str) -> bool {
+    let key = key.strip_suffix(ENCRYPTED_SUFFIX).unwrap_or(key);
+    key.starts_with(BACKUP_PREFIX) && key.ends_with(BACKUP_SUFFIX)
+}
+
+/// 列出备份存储中的全部备份，按时间从早到晚排序
+pub async fn list_backups(store: &dyn BackupStore) -> Result<Vec<BackupObject>> {
+    let mut backups: Vec<BackupObject> = store
+        .list()
+        .await?
+        .into_iter()
+        .filter(|object| is_backup_key(&object.key))
+        .collect();
+    // 文件名中的时间戳位数相同，按文件名排序即按时间排序
+    backups.sort_by(|a, b| a.key.cmp(&b.key));
+    Ok(backups)
+}
+
+/// 确认 `key` 存在于备份存储中，未指定时返回最新的备份
+pub async fn resolve_backup(store: &dyn BackupStore, key: Option<&str>) -> Result<String> {
+    let backups = list_backups(store).await?;
+    let found = match key {
+        Some(key) => backups.into_iter().find(|backup| backup.key == key),
+        None => backups.into_iter().next_back(),
+    };
+    found.map(|backup| backup.key).ok_or_else(|| {
+        AppError::Validation(match key {
+            Some(key) => format!("{}中没有备份文件 {}", store.describe(), key),
+            None => format!("{}中没有任何备份文件", store.describe()),
+        })
+        .into()
+    })
+}
+
+/// 将备份下载到备份目录，返回本地文件路径
+pub async fn download_backup(store: &dyn BackupStore, key: &str) -> Result<PathBuf> {
+    let backup_dir = backup_path();
+    std::fs::create_dir_all(&backup_dir).context("创建备份目录失败")?;
+
+    let path = Path::new(&backup_dir).join(format!("restore_{}", key));
+    info!("从{}下载备份文件 {}", store.describe(), key);
+    store.get(key, &path).await?;
+    Ok(path)
+}
+
+/// mongorestore 恢复的一个集合
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RestoredCollection {
+    /// 集合名（不含数据库名）
+    pub name: String,
+    /// 备份中的文档数
+    pub documents: u64,
+    /// 恢复失败的文档数
+    pub failures: u64,
+}
+
+/// 从 mongorestore 的日志中读取各集合恢复的文档数，
+/// 日志形如 `finished restoring maimap.arcades (2093 documents, 0 failures)`
+pub fn parse_restore_log(log: &str) -> Vec<RestoredCollection> {
+    const MARKER: &str = "finished restoring ";
+
+    log.lines()
+        .filter_map(|line| {
+            let rest = &line[line.find(MARKER)? + MARKER.len()..];
+            let (namespace, counts) = rest.split_once(" (")?;
+            let (_, name) = namespace.split_once('.')?;
+            let mut numbers = counts
+                .split(|c: char| !c.is_ascii_digit())
+                .filter(|part| !part.is_empty())
+                .map(|part| part.parse::<u64>());
+            Some(RestoredCollection {
+                name: name.to_string(),
+                documents: numbers.next()?.ok()?,
+                failures: numbers.next()?.ok()?,
+            })
+        })
+        .collect()
+}
+
+/// 将备份恢复到 `target_db`，并校验恢复后各集合的文档数与备份一致。
+///
+/// 原生格式的备份通过驱动导入，其余交给 mongorestore；后者在 `target_db` 与 `maimap` 不同时
+/// 通过 `--nsFrom`/`--nsTo` 改写数据库名，可用于恢复到预发布环境。
+/// 目标数据库中已有数据且未指定 `drop` 时，文档数会与备份不一致而校验失败。
+pub async fn restore_database(
+    archive: &Path,
+    target_db: &str,
+    drop: bool,
+) -> Result<Vec<RestoredCollection>> {
+    info!(
+        "从备份文件恢复数据库：{} -> {}",
+        archive.display(),
+        target_db
+    );
+
+    // 加密的备份先解密到临时文件，恢复后删除
+    let decrypted = match encrypted_key_id(archive)? {
+        Some(key_id) => {
+            info!("使用密钥 {} 解密备份", key_id);
+            let path = decrypted_path(archive);
+            decrypt_file(&BackupKeyring::from_env()?, archive, &path)?;
+            Some(path)
+        }
+        None => None,
+    };
+    let result = restore_plain(decrypted.as_deref().unwrap_or(archive), target_db, drop).await;
+    if let Some(path) = decrypted {
+        let _ = std::fs::remove_file(path);
+    }
+    result
+}
+
+/// 解密后的临时文件路径，与备份文件在同一目录
+pub(crate) fn decrypted_path(archive: &Path) -> PathBuf {
+    let name = archive
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let name = name.strip_suffix(ENCRYPTED_SUFFIX).unwrap_or(&name);
+    archive.with_file_name(format!("decrypted_{}", name))
+}
+
+async fn restore_plain(
+    archive: &Path,
+    target_db: &str,
+    drop: bool,
+) -> Result<Vec<RestoredCollection>> {
+    let restored = if native::is_native_archive(archive)? {
+        let db = get_mongodb_client().database(target_db);
+        native::import_native(&db, archive, drop).await?
+    } else {
+        mongorestore(archive, target_db, drop)?
+    };
+
+    verify_restore(target_db, &restored).await?;
+    Ok(restored)
+}
+
+fn mongorestore(archive: &Path, target_db: &str, drop: bool) -> Result<Vec<RestoredCollection>> {
+    let mut command = Command::new("mongorestore");
+    command
+        .arg(format!("--uri={}", database_uri()))
+        .arg(format!("--nsInclude={}.*", DB_NAME))
+        .arg("--gzip")
+        .arg(format!("--archive={}", archive.display()));
+    if target_db != DB_NAME {
+        command
+            .arg(format!("--nsFrom={}.*", DB_NAME))
+            .arg(format!("--nsTo={}.*", target_db));
+    }
+    if drop {
+        command.arg("--drop");
+    }
+
+    let output = command.output().context("执行恢复命令失败")?;
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    if !output.status.success() {
+        return Err(AppError::CommandExecution {
+            status: output.status,
+            stderr,
+        }
+        .into());
+    }
+
+    Ok(parse_restore_log(&stderr))
+}
+
+async fn verify_restore(target_db: &str, restored: &[RestoredCollection]) -> Result<()> {
+    if restored.is_empty() {
+        warn!("未能读取到备份中各集合的文档数，跳过校验");
+        return Ok(());
+    }
+
+    let db = get_mongodb_client().database(target_db);
+    let mut mismatches = Vec::new();
+    for collection in restored {
+        let count = db
+            .collection::<Document>(&collection.name)
+            .count_documents(doc! {})
+            .await?;
+        info!(
+            "集合 {}：备份中 {} 个文档，恢复后 {} 个文档，失败 {} 个",
+            collection.name, collection.documents, count, collection.failures
+        );
+        if collection.failures > 0 || count != collection.documents {
+            mismatches.push(collection.name.as_str());
+        }
+    }
+
+    if !mismatches.is_empty() {
+        return Err(AppError::BackupExecution(format!(
+            "恢复后以下集合的文档数与备份不一致：{}",
+            mismatches.join("、")
+        ))
+        .into());
+    }
+    Ok(())
+}
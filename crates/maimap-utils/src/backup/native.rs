@@ -0,0 +1,309 @@
+use super::RestoredCollection;
+use crate::db::{DateTime, Document, doc};
+use crate::errors::{AppError, Context, Result};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use futures_util::stream::StreamExt;
+use mongodb::Database;
+use mongodb::bson::{RawDocumentBuf, from_slice, to_vec};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::Path;
+use tracing::info;
+
+/// 原生备份文件解压后开头的标识
+const MAGIC: &[u8; 8] = b"MAIMAPNB";
+/// 原生备份的格式版本
+const FORMAT_VERSION: i32 = 1;
+/// 导入时每批写入的文档数
+const IMPORT_BATCH_SIZE: usize = 1000;
+
+// 每条记录由一个类型字节与一个 BSON 文档组成
+const RECORD_COLLECTION: u8 = b'C';
+const RECORD_DOCUMENT: u8 = b'D';
+const RECORD_MANIFEST: u8 = b'M';
+
+/// 原生备份中的一个集合
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CollectionManifest {
+    /// 集合名
+    pub name: String,
+    /// 文档数
+    pub count: u64,
+}
+
+/// 原生备份的清单，写在备份文件末尾，用于导入时校验备份是否完整
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ArchiveManifest {
+    /// 格式版本
+    pub format_version: i32,
+    /// 导出的数据库名
+    pub database: String,
+    /// 导出时间
+    pub created_at: DateTime,
+    /// 按导出顺序排列的集合
+    pub collections: Vec<CollectionManifest>,
+}
+
+/// 原生备份中的一条记录
+pub enum ArchiveRecord {
+    /// 之后的文档属于此集合
+    Collection(String),
+    Document(RawDocumentBuf),
+    Manifest(ArchiveManifest),
+}
+
+/// 写入原生备份：gzip 压缩的标识、逐条记录与末尾的清单
+pub struct ArchiveWriter<W: Write> {
+    inner: GzEncoder<W>,
+    collections: Vec<CollectionManifest>,
+}
+
+impl<W: Write> ArchiveWriter<W> {
+    pub fn new(writer: W) -> Result<Self> {
+        let mut inner = GzEncoder::new(writer, Compression::default());
+        inner.write_all(MAGIC)?;
+        Ok(Self {
+            inner,
+            collections: Vec::new(),
+        })
+    }
+
+    fn write_record(&mut self, kind: u8, bytes: &[u8]) -> Result<()> {
+        self.inner.write_all(&[kind])?;
+        self.inner.write_all(bytes)?;
+        Ok(())
+    }
+
+    /// 开始写入一个集合
+    pub fn begin_collection(&mut self, name: &str) -> Result<()> {
+        let header = to_vec(&doc! { "name": name })?;
+        self.write_record(RECORD_COLLECTION, &header)?;
+        self.collections.push(CollectionManifest {
+            name: name.to_string(),
+            count: 0,
+        });
+        Ok(())
+    }
+
+    /// 向当前集合写入一个文档
+    pub fn write_document(&mut self, document: &RawDocumentBuf) -> Result<()> {
+        let collection = self
+            .collections
+            .last_mut()
+            .ok_or_else(|| AppError::BackupExecution("写入文档前未指定集合".to_string()))?;
+        collection.count += 1;
+        self.write_record(RECORD_DOCUMENT, document.as_bytes())
+    }
+
+    /// 写入清单并结束压缩，返回写入的清单
+    pub fn finish(mut self, database: &str) -> Result<ArchiveManifest> {
+        let manifest = ArchiveManifest {
+            format_version: FORMAT_VERSION,
+            database: database.to_string(),
+            created_at: DateTime::now(),
+            collections: std::mem::take(&mut self.collections),
+        };
+        let bytes = to_vec(&manifest)?;
+        self.write_record(RECORD_MANIFEST, &bytes)?;
+        self.inner.finish()?.flush()?;
+        Ok(manifest)
+    }
+}
+
+/// 逐条读取原生备份
+pub struct ArchiveReader<R: Read> {
+    inner: GzDecoder<R>,
+}
+
+impl<R: Read> ArchiveReader<R> {
+    pub fn new(reader: R) -> Result<Self> {
+        let mut inner = GzDecoder::new(reader);
+        let mut magic = [0u8; 8];
+        inner.read_exact(&mut magic).context("读取备份文件头失败")?;
+        if &magic != MAGIC {
+            return Err(AppError::Parse("不是原生格式的备份文件".to_string()).into());
+        }
+        Ok(Self { inner })
+    }
+
+    /// 读取下一条记录，文件结束时返回 `None`
+    pub fn next_record(&mut self) -> Result<Option<ArchiveRecord>> {
+        let mut kind = [0u8; 1];
+        match self.inner.read_exact(&mut kind) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+
+        // BSON 文档的前 4 个字节是包括自身在内的总长度
+        let mut length = [0u8; 4];
+        self.inner.read_exact(&mut length)?;
+        let total = i32::from_le_bytes(length);
+        if total < 5 {
+            return Err(AppError::Parse(format!("备份中的文档长度无效：{}", total)).into());
+        }
+        let mut bytes = vec![0u8; total as usize];
+        bytes[..4].copy_from_slice(&length);
+        self.inner
+            .read_exact(&mut bytes[4..])
+            .context("备份文件不完整")?;
+
+        let record = match kind[0] {
+            RECORD_COLLECTION => {
+                let header: Document = from_slice(&bytes)?;
+                ArchiveRecord::Collection(header.get_str("name")?.to_string())
+            }
+            RECORD_DOCUMENT => ArchiveRecord::Document(RawDocumentBuf::from_bytes(bytes)?),
+            RECORD_MANIFEST => ArchiveRecord::Manifest(from_slice(&bytes)?),
+            other => {
+                return Err(AppError::Parse(format!("备份中的记录类型无效：{}", other)).into());
+            }
+        };
+        Ok(Some(record))
+    }
+}
+
+/// 判断备份文件是否为原生格式，mongodump 生成的备份返回 `false`
+pub fn is_native_archive(path: &Path) -> Result<bool> {
+    let file = File::open(path).with_context(|| format!("打开备份文件 {} 失败", path.display()))?;
+    let mut magic = [0u8; 8];
+    match GzDecoder::new(file).read_exact(&mut magic) {
+        Ok(()) => Ok(&magic == MAGIC),
+        Err(_) => Ok(false),
+    }
+}
+
+/// 读取整个原生备份并统计各集合的文档数，缺少末尾的清单时返回错误
+pub fn count_archive(path: &Path) -> Result<Vec<CollectionManifest>> {
+    let file = File::open(path).with_context(|| format!("打开备份文件 {} 失败", path.display()))?;
+    let mut reader = ArchiveReader::new(BufReader::new(file))?;
+
+    let mut collections: Vec<CollectionManifest> = Vec::new();
+    let mut complete = false;
+    while let Some(record) = reader.next_record()? {
+        match record {
+            ArchiveRecord::Collection(name) => {
+                collections.push(CollectionManifest { name, count: 0 })
+            }
+            ArchiveRecord::Document(_) => {
+                let collection = collections
+                    .last_mut()
+                    .ok_or_else(|| AppError::Parse("备份中的文档不属于任何集合".to_string()))?;
+                collection.count += 1;
+            }
+            ArchiveRecord::Manifest(_) => complete = true,
+        }
+    }
+
+    if !complete {
+        return Err(AppError::BackupExecution("备份文件缺少清单，可能不完整".to_string()).into());
+    }
+    Ok(collections)
+}
+
+/// 通过驱动逐个导出 `db` 中的集合到原生备份文件，不依赖 mongodump。
+///
+/// 只导出文档，索引由服务启动时或 `indexes` 命令按代码中的声明创建。
+pub async fn export_native(db: &Database, path: &Path) -> Result<ArchiveManifest> {
+    let file =
+        File::create(path).with_context(|| format!("创建备份文件 {} 失败", path.display()))?;
+    let mut writer = ArchiveWriter::new(BufWriter::new(file))?;
+
+    let mut names = db.list_collection_names().await?;
+    names.retain(|name| !name.starts_with("system."));
+    names.sort();
+
+    for name in &names {
+        writer.begin_collection(name)?;
+        let mut cursor = db.collection::<RawDocumentBuf>(name).find(doc! {}).await?;
+        while let Some(document) = cursor.next().await {
+            writer.write_document(&document?)?;
+        }
+    }
+
+    let manifest = writer.finish(db.name())?;
+    for collection in &manifest.collections {
+        info!("导出集合 {}：{} 个文档", collection.name, collection.count);
+    }
+    Ok(manifest)
+}
+
+/// 将原生备份导入 `db`，`drop` 为真时先删除备份中包含的集合。
+///
+/// 文件缺少末尾的清单时视为备份不完整并返回错误；返回的文档数以清单为准，
+/// 未能导入的文档计为失败，由调用方校验。
+pub async fn import_native(
+    db: &Database,
+    path: &Path,
+    drop: bool,
+) -> Result<Vec<RestoredCollection>> {
+    let file = File::open(path).with_context(|| format!("打开备份文件 {} 失败", path.display()))?;
+    let mut reader = ArchiveReader::new(BufReader::new(file))?;
+
+    let mut restored: Vec<RestoredCollection> = Vec::new();
+    let mut batch = Vec::new();
+    let mut manifest = None;
+    while let Some(record) = reader.next_record()? {
+        match record {
+            ArchiveRecord::Collection(name) => {
+                flush_batch(db, &mut restored, &mut batch).await?;
+                if drop {
+                    db.collection::<Document>(&name).drop().await?;
+                }
+                info!("导入集合 {}", name);
+                restored.push(RestoredCollection {
+                    name,
+                    documents: 0,
+                    failures: 0,
+                });
+            }
+            ArchiveRecord::Document(document) => {
+                batch.push(document);
+                if batch.len() >= IMPORT_BATCH_SIZE {
+                    flush_batch(db, &mut restored, &mut batch).await?;
+                }
+            }
+            ArchiveRecord::Manifest(found) => manifest = Some(found),
+        }
+    }
+    flush_batch(db, &mut restored, &mut batch).await?;
+
+    let manifest = manifest
+        .ok_or_else(|| AppError::BackupExecution("备份文件缺少清单，可能不完整".to_string()))?;
+    for collection in &mut restored {
+        let expected = manifest
+            .collections
+            .iter()
+            .find(|entry| entry.name == collection.name)
+            .map(|entry| entry.count)
+            .unwrap_or_default();
+        // 以清单中的数量为准，缺少的文档计为失败
+        collection.failures = expected.saturating_sub(collection.documents);
+        collection.documents = expected;
+    }
+    Ok(restored)
+}
+
+async fn flush_batch(
+    db: &Database,
+    restored: &mut [RestoredCollection],
+    batch: &mut Vec<RawDocumentBuf>,
+) -> Result<()> {
+    if batch.is_empty() {
+        return Ok(());
+    }
+    let collection = restored
+        .last_mut()
+        .ok_or_else(|| AppError::Parse("备份中的文档不属于任何集合".to_string()))?;
+    let inserted = db
+        .collection::<RawDocumentBuf>(&collection.name)
+        .insert_many(batch.drain(..))
+        .await?
+        .inserted_ids
+        .len();
+    collection.documents += inserted as u64;
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use super::manifest::manifest_key;
+use super::store::BackupStore;
+use super::{BACKUP_PREFIX, list_backups};
+use crate::env::{backup_keep_daily, backup_keep_monthly, backup_keep_weekly};
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Local, NaiveDate, TimeZone, Utc};
+use std::collections::HashSet;
+use tracing::info;
+
+/// 将日期映射到所属的日、周或月
+type Period = fn(NaiveDate) -> (i32, u32);
+
+/// 备份保留策略：保留最近 `daily` 天、`weekly` 周与 `monthly` 个月中每个周期最新的一份备份。
+///
+/// 三者均为 0 时不删除任何备份。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    pub daily: usize,
+    pub weekly: usize,
+    pub monthly: usize,
+}
+
+impl RetentionPolicy {
+    pub fn from_env() -> Self {
+        Self {
+            daily: backup_keep_daily(),
+            weekly: backup_keep_weekly(),
+            monthly: backup_keep_monthly(),
+        }
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.daily == 0 && self.weekly == 0 && self.monthly == 0
+    }
+
+    /// 返回按策略应删除的备份文件名。
+    ///
+    /// 最新的一份备份总会保留；文件名中无法读取时间戳的文件不会被删除。
+    /// 日、周、月按 `tz` 时区划分。
+    pub fn expired<Tz: TimeZone>(&self, keys: &[String], tz: &Tz) -> Vec<String> {
+        if self.is_disabled() {
+            return Vec::new();
+        }
+
+        let mut backups: Vec<(&String, DateTime<Utc>)> = keys
+            .iter()
+            .filter_map(|key| Some((key, backup_time(key)?)))
+            .collect();
+        // 从新到旧，使每个周期中先遇到的是最新的备份
+        backups.sort_by_key(|(_, time)| std::cmp::Reverse(*time));
+        let backups: Vec<(&String, NaiveDate)> = backups
+            .into_iter()
+            .map(|(key, time)| (key, time.with_timezone(tz).date_naive()))
+            .collect();
+
+        let mut keep: HashSet<&String> = backups.first().map(|(key, _)| *key).into_iter().collect();
+        let periods: [(usize, Period); 3] = [
+            (self.daily, |date| (date.year(), date.ordinal())),
+            (self.weekly, |date| {
+                let week = date.iso_week();
+                (week.year(), week.week())
+            }),
+            (self.monthly, |date| (date.year(), date.month())),
+        ];
+        for (count, period) in periods {
+            let mut seen = HashSet::new();
+            for (key, date) in &backups {
+                if seen.len() >= count {
+                    break;
+                }
+                if seen.insert(period(*date)) {
+                    keep.insert(key);
+                }
+            }
+        }
+
+        backups
+            .iter()
+            .filter(|(key, _)| !keep.contains(key))
+            .map(|(key, _)| key.to_string())
+            .collect()
+    }
+}
+
+/// 从 `maimap_<时间戳>.gz`、`maimap_<时间戳>.bson.gz` 及其加密后的 `.enc` 文件名中读取备份时间
+fn backup_time(key: &str) -> Option<DateTime<Utc>> {
+    let rest = key.strip_prefix(BACKUP_PREFIX)?;
+    let timestamp = rest.split('.').next()?.parse().ok()?;
+    DateTime::from_timestamp(timestamp, 0)
+}
+
+/// 按保留策略删除 `store` 中过期的备份，返回删除（或 `dry_run` 时将删除）的文件名
+pub async fn prune_backups(
+    store: &dyn BackupStore,
+    policy: &RetentionPolicy,
+    dry_run: bool,
+) -> Result<Vec<String>> {
+    if policy.is_disabled() {
+        info!("未设置备份保留策略，不清理{}", store.describe());
+        return Ok(Vec::new());
+    }
+
+    let keys: Vec<String> = list_backups(store)
+        .await?
+        .into_iter()
+        .map(|backup| backup.key)
+        .collect();
+    let expired = policy.expired(&keys, &Local);
+    for key in &expired {
+        if dry_run {
+            info!("[dry-run] 将删除{}中的过期备份 {}", store.describe(), key);
+        } else {
+            info!("删除{}中的过期备份 {}", store.describe(), key);
+            store.delete(key).await?;
+            store.delete(&manifest_key(key)).await?;
+        }
+    }
+    info!(
+        "{}中共 {} 份备份，保留 {} 份，{}删除 {} 份",
+        store.describe(),
+        keys.len(),
+        keys.len() - expired.len(),
+        if dry_run { "将" } else { "已" },
+        expired.len()
+    );
+    Ok(expired)
+}
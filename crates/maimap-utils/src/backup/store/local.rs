@@ -0,0 +1,87 @@
+use super::{BackupObject, BackupStore};
+use crate::env::backup_local_path;
+use crate::errors::{AppError, Context, Result};
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+
+/// 将备份保存在本地目录中，适合没有对象存储的部署与测试
+pub struct LocalStore {
+    root: PathBuf,
+}
+
+impl LocalStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(backup_local_path())
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf> {
+        // 不允许通过文件名访问目录之外的文件
+        if key.is_empty() || key.contains(['/', '\\']) || key == "." || key == ".." {
+            return Err(AppError::Validation(format!("无效的备份文件名：{}", key)).into());
+        }
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl BackupStore for LocalStore {
+    fn describe(&self) -> String {
+        format!("本地目录 {}", self.root.display())
+    }
+
+    async fn put(&self, key: &str, file: &Path) -> Result<()> {
+        let target = self.path(key)?;
+        std::fs::create_dir_all(&self.root).context("创建备份目录失败")?;
+        // 备份文件已在存储目录中时无需复制
+        if std::fs::canonicalize(file).ok() == std::fs::canonicalize(&target).ok() {
+            return Ok(());
+        }
+        std::fs::copy(file, &target)
+            .with_context(|| format!("复制备份文件到 {} 失败", target.display()))?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str, file: &Path) -> Result<()> {
+        let source = self.path(key)?;
+        std::fs::copy(&source, file)
+            .with_context(|| format!("读取备份文件 {} 失败", source.display()))?;
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<BackupObject>> {
+        let entries = match std::fs::read_dir(&self.root) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(AppError::Io(e).into()),
+        };
+
+        let mut objects = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if !metadata.is_file() {
+                continue;
+            }
+            if let Some(key) = entry.file_name().to_str() {
+                objects.push(BackupObject {
+                    key: key.to_string(),
+                    size: metadata.len(),
+                });
+            }
+        }
+        objects.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(objects)
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        match std::fs::remove_file(self.path(key)?) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(AppError::Io(e).into()),
+        }
+    }
+}
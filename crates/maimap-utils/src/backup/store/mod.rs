@@ -0,0 +1,78 @@
+mod local;
+mod oss;
+mod s3;
+
+pub use local::LocalStore;
+pub use oss::OssStore;
+pub use s3::{S3Store, parse_list_objects};
+
+use crate::env::backup_store;
+use crate::errors::{AppError, Result};
+use async_trait::async_trait;
+use std::path::Path;
+use std::str::FromStr;
+
+/// 备份存储中的一个文件
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BackupObject {
+    /// 文件名
+    pub key: String,
+    /// 文件大小（字节）
+    pub size: u64,
+}
+
+/// 保存备份文件的位置
+#[async_trait]
+pub trait BackupStore: Send + Sync {
+    /// 用于日志的存储描述
+    fn describe(&self) -> String;
+
+    /// 将本地文件 `file` 上传为 `key`
+    async fn put(&self, key: &str, file: &Path) -> Result<()>;
+
+    /// 将 `key` 下载到本地文件 `file`
+    async fn get(&self, key: &str, file: &Path) -> Result<()>;
+
+    /// 列出全部备份文件，按文件名排序
+    async fn list(&self) -> Result<Vec<BackupObject>>;
+
+    /// 删除 `key`，文件不存在时不报错
+    async fn delete(&self, key: &str) -> Result<()>;
+}
+
+/// 备份存储的类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackupStoreKind {
+    /// 本地目录
+    Local,
+    /// S3 兼容的对象存储，如 MinIO、Cloudflare R2
+    S3,
+    /// 阿里云 OSS
+    Oss,
+}
+
+impl FromStr for BackupStoreKind {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "local" => Ok(Self::Local),
+            "s3" => Ok(Self::S3),
+            "oss" => Ok(Self::Oss),
+            other => Err(AppError::Configuration(format!(
+                "未知的备份存储类型 '{}'，可选 local、s3、oss",
+                other
+            ))),
+        }
+    }
+}
+
+/// 根据 `BACKUP_STORE` 创建备份存储
+pub fn backup_store_from_env() -> Result<Box<dyn BackupStore>> {
+    let store: Box<dyn BackupStore> = match backup_store().parse::<BackupStoreKind>()? {
+        BackupStoreKind::Local => Box::new(LocalStore::from_env()),
+        BackupStoreKind::S3 => Box::new(S3Store::from_env()?),
+        BackupStoreKind::Oss => Box::new(OssStore::from_env()?),
+    };
+    Ok(store)
+}
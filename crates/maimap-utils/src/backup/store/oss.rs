@@ -0,0 +1,118 @@
+use super::{BackupObject, BackupStore};
+use crate::env::{
+    aliyun_acc_key_id, aliyun_acc_key_secret, aliyun_oss_bucket_name, aliyun_oss_endpoint,
+    aliyun_oss_region,
+};
+use crate::errors::{AppError, Result};
+use ali_oss_rs::Client;
+use ali_oss_rs::bucket::BucketOperations;
+use ali_oss_rs::bucket_common::ListObjectsOptionsBuilder;
+use ali_oss_rs::object::ObjectOperations;
+use async_trait::async_trait;
+use std::path::Path;
+
+/// 将备份上传到阿里云 OSS
+pub struct OssStore {
+    client: Client,
+    bucket: String,
+}
+
+impl OssStore {
+    pub fn new(client: Client, bucket: impl Into<String>) -> Self {
+        Self {
+            client,
+            bucket: bucket.into(),
+        }
+    }
+
+    /// 缺少任一阿里云环境变量时返回配置错误，而不是创建一个无法使用的客户端
+    pub fn from_env() -> Result<Self> {
+        let vars = [
+            ("ALI_ACCESS_KEY_ID", aliyun_acc_key_id()),
+            ("ALI_ACCESS_KEY_SECRET", aliyun_acc_key_secret()),
+            ("ALI_OSS_REGION", aliyun_oss_region()),
+            ("ALI_OSS_ENDPOINT", aliyun_oss_endpoint()),
+            ("ALI_OSS_BUCKET_NAME", aliyun_oss_bucket_name()),
+        ];
+        let missing: Vec<&str> = vars
+            .iter()
+            .filter(|(_, value)| value.is_empty())
+            .map(|(name, _)| *name)
+            .collect();
+        if !missing.is_empty() {
+            return Err(AppError::Configuration(format!(
+                "使用 OSS 备份存储需要设置 {}",
+                missing.join("、")
+            ))
+            .into());
+        }
+
+        let [key_id, key_secret, region, endpoint, bucket] = vars.map(|(_, value)| value);
+        Ok(Self::new(
+            Client::new(key_id, key_secret, region, endpoint),
+            bucket,
+        ))
+    }
+}
+
+fn oss_error(e: ali_oss_rs::error::Error) -> AppError {
+    AppError::OssOperation(e.to_string())
+}
+
+#[async_trait]
+impl BackupStore for OssStore {
+    fn describe(&self) -> String {
+        format!("阿里云 OSS {}", self.bucket)
+    }
+
+    async fn put(&self, key: &str, file: &Path) -> Result<()> {
+        self.client
+            .put_object_from_file(&self.bucket, key, file, None)
+            .await
+            .map_err(oss_error)?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str, file: &Path) -> Result<()> {
+        self.client
+            .get_object_to_file(&self.bucket, key, file, None)
+            .await
+            .map_err(oss_error)?;
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<BackupObject>> {
+        let mut objects = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let mut options = ListObjectsOptionsBuilder::new().max_keys(1000);
+            if let Some(token) = continuation_token.take() {
+                options = options.continuation_token(token);
+            }
+            let result = self
+                .client
+                .list_objects(&self.bucket, Some(options.build()))
+                .await
+                .map_err(oss_error)?;
+
+            objects.extend(result.contents.into_iter().map(|object| BackupObject {
+                key: object.key,
+                size: object.size,
+            }));
+            match result.next_continuation_token {
+                Some(token) if result.is_truncated => continuation_token = Some(token),
+                _ => break,
+            }
+        }
+        objects.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(objects)
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.client
+            .delete_object(&self.bucket, key, None)
+            .await
+            .map_err(oss_error)?;
+        Ok(())
+    }
+}
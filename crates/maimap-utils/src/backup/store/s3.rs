@@ -0,0 +1,471 @@
+use super::{BackupObject, BackupStore};
+use crate::env::{s3_access_key_id, s3_bucket, s3_endpoint, s3_region, s3_secret_access_key};
+use crate::errors::{AppError, Context, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use quick_xml::Reader;
+use quick_xml::events::Event;
+use reqwest::{Method, StatusCode};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use tracing::warn;
+
+/// 分块上传时每块的大小，超过一块的文件分块上传；S3 要求除最后一块外不小于 5 MiB，
+/// 单次 PUT 最大只能上传 5 GiB
+const PART_SIZE: u64 = 8 * 1024 * 1024;
+/// 一次分块上传最多的块数
+const MAX_PARTS: u64 = 10_000;
+
+/// 校验 `S3_ENDPOINT` 并返回签名用的 `host`。
+///
+/// 签名只覆盖 `/<bucket>/<key>`，地址中带路径前缀会导致 SignatureDoesNotMatch，因此直接拒绝。
+fn endpoint_host(endpoint: &str) -> Result<String> {
+    let url = reqwest::Url::parse(endpoint).context("S3_ENDPOINT 不是有效的地址")?;
+    if url.path() != "/" || url.query().is_some() || url.fragment().is_some() {
+        return Err(AppError::Configuration(format!(
+            "S3_ENDPOINT 只能包含协议、主机与端口，不能带路径或查询参数：{}",
+            endpoint
+        ))
+        .into());
+    }
+    match (url.host_str(), url.port()) {
+        (Some(host), Some(port)) => Ok(format!("{}:{}", host, port)),
+        (Some(host), None) => Ok(host.to_string()),
+        (None, _) => Err(AppError::Configuration("S3_ENDPOINT 缺少主机名".to_string()).into()),
+    }
+}
+
+/// 将备份上传到 S3 兼容的对象存储（AWS S3、MinIO、Cloudflare R2 等）。
+///
+/// 使用路径风格的地址 `<endpoint>/<bucket>/<key>` 与 AWS Signature V4 签名。
+pub struct S3Store {
+    http: reqwest::Client,
+    endpoint: String,
+    region: String,
+    bucket: String,
+    access_key_id: String,
+    secret_access_key: String,
+}
+
+impl S3Store {
+    pub fn new(
+        endpoint: impl Into<String>,
+        region: impl Into<String>,
+        bucket: impl Into<String>,
+        access_key_id: impl Into<String>,
+        secret_access_key: impl Into<String>,
+    ) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            endpoint: endpoint.into().trim_end_matches('/').to_string(),
+            region: region.into(),
+            bucket: bucket.into(),
+            access_key_id: access_key_id.into(),
+            secret_access_key: secret_access_key.into(),
+        }
+    }
+
+    /// 缺少任一 S3 环境变量时返回配置错误
+    pub fn from_env() -> Result<Self> {
+        let vars = [
+            ("S3_ENDPOINT", s3_endpoint()),
+            ("S3_BUCKET", s3_bucket()),
+            ("S3_ACCESS_KEY_ID", s3_access_key_id()),
+            ("S3_SECRET_ACCESS_KEY", s3_secret_access_key()),
+        ];
+        let missing: Vec<&str> = vars
+            .iter()
+            .filter(|(_, value)| value.is_empty())
+            .map(|(name, _)| *name)
+            .collect();
+        if !missing.is_empty() {
+            return Err(AppError::Configuration(format!(
+                "使用 S3 备份存储需要设置 {}",
+                missing.join("、")
+            ))
+            .into());
+        }
+
+        let [endpoint, bucket, access_key_id, secret_access_key] = vars.map(|(_, value)| value);
+        endpoint_host(&endpoint)?;
+        Ok(Self::new(
+            endpoint,
+            s3_region(),
+            bucket,
+            access_key_id,
+            secret_access_key,
+        ))
+    }
+
+    /// 发送签名后的请求，`query` 中的参数无需预先编码
+    async fn send(
+        &self,
+        method: Method,
+        key: Option<&str>,
+        query: &[(&str, &str)],
+        body: Vec<u8>,
+    ) -> Result<reqwest::Response> {
+        let mut path = format!("/{}", uri_encode(&self.bucket, false));
+        if let Some(key) = key {
+            path.push('/');
+            path.push_str(&uri_encode(key, true));
+        }
+
+        let mut query: Vec<(String, String)> = query
+            .iter()
+            .map(|(k, v)| (uri_encode(k, false), uri_encode(v, false)))
+            .collect();
+        query.sort();
+        let query = query
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let url = if query.is_empty() {
+            format!("{}{}", self.endpoint, path)
+        } else {
+            format!("{}{}?{}", self.endpoint, path, query)
+        };
+        let host = endpoint_host(&self.endpoint)?;
+        let parsed = reqwest::Url::parse(&url).context("S3_ENDPOINT 不是有效的地址")?;
+
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let payload_hash = hex::encode(Sha256::digest(&body));
+        let authorization = self.authorization(
+            method.as_str(),
+            &path,
+            &query,
+            &host,
+            &amz_date,
+            &payload_hash,
+        );
+
+        let response = self
+            .http
+            .request(method, parsed)
+            .header("x-amz-date", &amz_date)
+            .header("x-amz-content-sha256", &payload_hash)
+            .header("authorization", authorization)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| AppError::BackupStore(e.to_string()))?;
+        Ok(response)
+    }
+
+    /// 分块上传 `file`，每次只在内存中保留一块；失败时取消上传，避免残留的分块占用存储
+    async fn put_multipart(&self, key: &str, file: &Path, size: u64) -> Result<()> {
+        let response = self
+            .send(Method::POST, Some(key), &[("uploads", "")], Vec::new())
+            .await?;
+        let body = check_status(response, "开始分块上传")
+            .await?
+            .text()
+            .await
+            .map_err(|e| AppError::BackupStore(e.to_string()))?;
+        let upload_id = parse_upload_id(&body)?;
+
+        let result = self.upload_parts(key, file, size, &upload_id).await;
+        if result.is_err() {
+            let query = [("uploadId", upload_id.as_str())];
+            let aborted = match self
+                .send(Method::DELETE, Some(key), &query, Vec::new())
+                .await
+            {
+                Ok(response) => check_status(response, "取消分块上传").await.map(|_| ()),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = aborted {
+                warn!("取消分块上传 {} 失败：{:#}", key, e);
+            }
+        }
+        result
+    }
+
+    async fn upload_parts(&self, key: &str, file: &Path, size: u64, upload_id: &str) -> Result<()> {
+        let part_size = PART_SIZE.max(size.div_ceil(MAX_PARTS));
+        let mut reader =
+            File::open(file).with_context(|| format!("读取备份文件 {} 失败", file.display()))?;
+        let mut etags = Vec::new();
+        loop {
+            let mut part = Vec::with_capacity(part_size as usize);
+            (&mut reader)
+                .take(part_size)
+                .read_to_end(&mut part)
+                .with_context(|| format!("读取备份文件 {} 失败", file.display()))?;
+            if part.is_empty() {
+                break;
+            }
+
+            let number = (etags.len() + 1).to_string();
+            let query = [("partNumber", number.as_str()), ("uploadId", upload_id)];
+            let response = self.send(Method::PUT, Some(key), &query, part).await?;
+            let response = check_status(response, "上传分块").await?;
+            let etag = response
+                .headers()
+                .get("etag")
+                .and_then(|etag| etag.to_str().ok())
+                .ok_or_else(|| AppError::BackupStore("上传分块的响应缺少 ETag".to_string()))?;
+            etags.push(etag.to_string());
+        }
+
+        let parts: String = etags
+            .iter()
+            .enumerate()
+            .map(|(i, etag)| {
+                format!(
+                    "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                    i + 1,
+                    etag
+                )
+            })
+            .collect();
+        let body = format!(
+            "<CompleteMultipartUpload>{}</CompleteMultipartUpload>",
+            parts
+        );
+        let response = self
+            .send(
+                Method::POST,
+                Some(key),
+                &[("uploadId", upload_id)],
+                body.into_bytes(),
+            )
+            .await?;
+        let body = check_status(response, "完成分块上传")
+            .await?
+            .text()
+            .await
+            .map_err(|e| AppError::BackupStore(e.to_string()))?;
+        // 完成分块上传时出错也可能返回 200，错误写在响应内容中
+        if body.contains("<Error>") {
+            return Err(AppError::BackupStore(format!("完成分块上传失败：{}", body)).into());
+        }
+        Ok(())
+    }
+
+    /// 计算 AWS Signature V4 的 `Authorization` 请求头
+    fn authorization(
+        &self,
+        method: &str,
+        path: &str,
+        query: &str,
+        host: &str,
+        amz_date: &str,
+        payload_hash: &str,
+    ) -> String {
+        let date = &amz_date[..8];
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method, path, query, host, payload_hash, amz_date, signed_headers, payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let mut key = hmac_sha256(
+            format!("AWS4{}", self.secret_access_key).as_bytes(),
+            date.as_bytes(),
+        );
+        for part in [self.region.as_str(), "s3", "aws4_request"] {
+            key = hmac_sha256(&key, part.as_bytes());
+        }
+        let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key_id, scope, signed_headers, signature
+        )
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC 接受任意长度的密钥");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// 按 S3 的规则编码 URI，`keep_slash` 为真时保留路径中的 `/`
+fn uri_encode(value: &str, keep_slash: bool) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b'/' if keep_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// 请求失败时附带响应内容，便于排查签名或权限问题
+async fn check_status(response: reqwest::Response, action: &str) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(AppError::BackupStore(format!("{}失败：HTTP {}，{}", action, status, body)).into())
+}
+
+/// 读取 CreateMultipartUpload 返回内容中的 `UploadId`
+fn parse_upload_id(xml: &str) -> Result<String> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut in_upload_id = false;
+    loop {
+        match reader
+            .read_event()
+            .map_err(|e| AppError::Parse(e.to_string()))?
+        {
+            Event::Start(tag) => in_upload_id = tag.local_name().as_ref() == b"UploadId",
+            Event::End(_) => in_upload_id = false,
+            Event::Text(text) if in_upload_id => {
+                return Ok(text
+                    .unescape()
+                    .map_err(|e| AppError::Parse(e.to_string()))?
+                    .to_string());
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Err(AppError::BackupStore(format!("开始分块上传的响应缺少 UploadId：{}", xml)).into())
+}
+
+/// 解析 ListObjectsV2 的返回内容，返回其中的文件与下一页的 continuation token
+pub fn parse_list_objects(xml: &str) -> Result<(Vec<BackupObject>, Option<String>)> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut objects = Vec::new();
+    let mut next_token = None;
+    let mut truncated = false;
+    let mut path: Vec<String> = Vec::new();
+    let mut key = None;
+    let mut size = 0;
+
+    loop {
+        match reader
+            .read_event()
+            .map_err(|e| AppError::Parse(e.to_string()))?
+        {
+            Event::Start(tag) => {
+                path.push(String::from_utf8_lossy(tag.local_name().as_ref()).to_string());
+            }
+            Event::End(_) => {
+                if path.last().map(String::as_str) == Some("Contents") {
+                    if let Some(key) = key.take() {
+                        objects.push(BackupObject { key, size });
+                    }
+                    size = 0;
+                }
+                path.pop();
+            }
+            Event::Text(text) => {
+                let text = text
+                    .unescape()
+                    .map_err(|e| AppError::Parse(e.to_string()))?
+                    .to_string();
+                match path.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+                    [.., "Contents", "Key"] => key = Some(text),
+                    [.., "Contents", "Size"] => size = text.parse().unwrap_or(0),
+                    [_, "IsTruncated"] => truncated = text == "true",
+                    [_, "NextContinuationToken"] => next_token = Some(text),
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok((objects, next_token.filter(|_| truncated)))
+}
+
+#[async_trait]
+impl BackupStore for S3Store {
+    fn describe(&self) -> String {
+        format!("S3 {}/{}", self.endpoint, self.bucket)
+    }
+
+    /// 不超过一块的文件直接上传，更大的文件分块上传
+    async fn put(&self, key: &str, file: &Path) -> Result<()> {
+        let size = std::fs::metadata(file)
+            .with_context(|| format!("读取备份文件 {} 失败", file.display()))?
+            .len();
+        if size > PART_SIZE {
+            return self.put_multipart(key, file, size).await;
+        }
+
+        let body =
+            std::fs::read(file).with_context(|| format!("读取备份文件 {} 失败", file.display()))?;
+        let response = self.send(Method::PUT, Some(key), &[], body).await?;
+        check_status(response, "上传备份文件").await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str, file: &Path) -> Result<()> {
+        let response = self.send(Method::GET, Some(key), &[], Vec::new()).await?;
+        let response = check_status(response, "下载备份文件").await?;
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| AppError::BackupStore(e.to_string()))?;
+        std::fs::write(file, bytes)
+            .with_context(|| format!("写入备份文件 {} 失败", file.display()))?;
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<BackupObject>> {
+        let mut objects = Vec::new();
+        let mut continuation_token: Option<String> = None;
+        loop {
+            let mut query = vec![("list-type", "2")];
+            if let Some(token) = &continuation_token {
+                query.push(("continuation-token", token));
+            }
+            let response = self.send(Method::GET, None, &query, Vec::new()).await?;
+            let body = check_status(response, "列出备份文件")
+                .await?
+                .text()
+                .await
+                .map_err(|e| AppError::BackupStore(e.to_string()))?;
+
+            let (page, next_token) = parse_list_objects(&body)?;
+            objects.extend(page);
+            match next_token {
+                Some(token) => continuation_token = Some(token),
+                None => break,
+            }
+        }
+        objects.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(objects)
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let response = self
+            .send(Method::DELETE, Some(key), &[], Vec::new())
+            .await?;
+        // 删除不存在的文件时 S3 返回 204，部分兼容实现返回 404
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(());
+        }
+        check_status(response, "删除备份文件").await?;
+        Ok(())
+    }
+}
@@ -3,7 +3,9 @@ use futures_util::stream::StreamExt;
 
 use anyhow::Result;
 
-pub use crate::types::Arcade;
+pub use crate::types::{
+    Arcade, ArcadeEvent, GeocodeCacheEntry, GeocodeFailure, JobStatus, ScrapeRun,
+};
 pub use mongodb::bson::Bson;
 pub use mongodb::bson::Bson::Int32;
 pub use mongodb::bson::Bson::ObjectId;
@@ -12,9 +14,13 @@ pub use mongodb::bson::Decimal128;
 pub use mongodb::bson::Document;
 pub use mongodb::bson::doc;
 pub use mongodb::bson::to_bson;
+use mongodb::error::{TRANSIENT_TRANSACTION_ERROR, UNKNOWN_TRANSACTION_COMMIT_RESULT};
 pub use mongodb::options::Collation;
-pub use mongodb::{Client, Collection};
+pub use mongodb::{Client, Collection, Database};
+use std::future::Future;
 use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tracing::warn;
 
 pub static MONGODB_CLIENT: OnceLock<Client> = OnceLock::new();
 
@@ -41,7 +47,7 @@ pub fn get_mongodb_client() -> &'static Client {
     MONGODB_CLIENT.get().unwrap()
 }
 
-pub async fn get_max_arcade_id() -> Result<i32> {
+async fn get_max_arcade_id() -> Result<i32> {
     let client = get_mongodb_client();
     let collection: Collection<Arcade> = client.database(DB_NAME).collection("arcades");
     let options = mongodb::options::FindOneOptions::builder()
@@ -55,14 +61,101 @@ pub async fn get_max_arcade_id() -> Result<i32> {
     }
 }
 
-pub async fn insert_many_arcades(arcades: Vec<Arcade>) -> Result<(), mongodb::error::Error> {
-    if arcades.is_empty() {
-        return Ok(());
+/// 从 `counters` 集合原子地分配 `count` 个连续的机厅ID。
+///
+/// 分配前用 `$max` 将计数器提升到现有最大ID，保证绕过计数器写入的机厅不会与新ID冲突。
+pub async fn allocate_arcade_ids(count: i32) -> Result<Vec<i32>> {
+    if count <= 0 {
+        return Ok(Vec::new());
     }
 
     let client = get_mongodb_client();
-    let collection: Collection<Arcade> = client.database(DB_NAME).collection("arcades");
-    collection.insert_many(arcades).await?;
+    let collection: Collection<Document> = client.database(DB_NAME).collection("counters");
+    let filter = doc! { "_id": "arcade_id" };
+
+    let max_id = get_max_arcade_id().await?;
+    collection
+        .update_one(filter.clone(), doc! { "$max": { "seq": max_id } })
+        .upsert(true)
+        .await?;
+
+    let counter = collection
+        .find_one_and_update(filter, doc! { "$inc": { "seq": count } })
+        .return_document(mongodb::options::ReturnDocument::After)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("未找到机厅ID计数器"))?;
+    // $max 可能把计数器写成 Int64
+    let last = i32::try_from(get_integer(&counter, "seq")?)?;
+
+    Ok((last - count + 1..=last).collect())
+}
+
+pub async fn insert_scrape_run(run: &ScrapeRun) -> Result<(), mongodb::error::Error> {
+    let client = get_mongodb_client();
+    let collection: Collection<ScrapeRun> = client.database(DB_NAME).collection("scrape_runs");
+    collection.insert_one(run).await?;
+    Ok(())
+}
+
+pub async fn get_geocode_cache(address: &str) -> Result<Option<GeocodeCacheEntry>> {
+    let client = get_mongodb_client();
+    let collection: Collection<GeocodeCacheEntry> =
+        client.database(DB_NAME).collection("geocode_cache");
+    Ok(collection.find_one(doc! { "_id": address }).await?)
+}
+
+/// 缓存解析成功的地址，并将其从重试队列中移除
+pub async fn save_geocode_cache(entry: &GeocodeCacheEntry) -> Result<()> {
+    let client = get_mongodb_client();
+    let database = client.database(DB_NAME);
+    let collection: Collection<GeocodeCacheEntry> = database.collection("geocode_cache");
+    collection
+        .replace_one(doc! { "_id": &entry.address }, entry)
+        .upsert(true)
+        .await?;
+
+    let queue: Collection<Document> = database.collection("geocode_queue");
+    queue.delete_one(doc! { "_id": &entry.address }).await?;
+    Ok(())
+}
+
+/// 读取重试队列中的全部地址，按最近一次尝试的时间排序
+pub async fn get_geocode_queue() -> Result<Vec<GeocodeFailure>> {
+    let client = get_mongodb_client();
+    let collection: Collection<GeocodeFailure> =
+        client.database(DB_NAME).collection("geocode_queue");
+
+    let find_options = mongodb::options::FindOptions::builder()
+        .sort(doc! { "last_attempt_at": 1 })
+        .build();
+
+    let mut cursor = collection.find(doc! {}).with_options(find_options).await?;
+    let mut failures = Vec::new();
+    while let Some(result) = cursor.next().await {
+        failures.push(result?);
+    }
+
+    Ok(failures)
+}
+
+/// 将解析失败的地址加入重试队列，已存在时累加失败次数
+pub async fn record_geocode_failure(address: &str, arcade_name: &str, error: &str) -> Result<()> {
+    let client = get_mongodb_client();
+    let collection: Collection<Document> = client.database(DB_NAME).collection("geocode_queue");
+    collection
+        .update_one(
+            doc! { "_id": address },
+            doc! {
+                "$set": {
+                    "arcade_name": arcade_name,
+                    "error": error,
+                    "last_attempt_at": DateTime::now(),
+                },
+                "$inc": { "attempts": 1 },
+            },
+        )
+        .upsert(true)
+        .await?;
     Ok(())
 }
 
@@ -84,21 +177,344 @@ pub async fn get_all_arcades() -> Result<Vec<Arcade>> {
     Ok(arcades)
 }
 
-pub async fn update_arcade(arcade: &Arcade) -> Result<()> {
+/// 一次爬取需要写入数据库的全部变更
+#[derive(Default)]
+pub struct ArcadeWrites {
+    /// 按 `arcade_id` 整体替换的已有机厅（更新、重新开放、关闭与缺失计数）
+    pub replaced: Vec<Arcade>,
+    /// 新增的机厅
+    pub inserted: Vec<Arcade>,
+    /// 机厅状态变化记录
+    pub events: Vec<ArcadeEvent>,
+}
+
+impl ArcadeWrites {
+    pub fn is_empty(&self) -> bool {
+        self.replaced.is_empty() && self.inserted.is_empty() && self.events.is_empty()
+    }
+}
+
+/// `bulkWrite` 命令要求的最低 wire version（MongoDB 8.0）
+const BULK_WRITE_MIN_WIRE_VERSION: i32 = 25;
+
+/// 不支持 `bulkWrite` 时，每条 `update` 命令替换的机厅数
+const REPLACE_BATCH_SIZE: usize = 500;
+
+/// 事务因临时错误失败时的最长重试时间，与驱动 `with_transaction` 的默认值一致
+const TRANSACTION_RETRY_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// 数据库部署支持的写入方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WriteCapabilities {
+    /// 是否为副本集，只有副本集（或分片集群）支持事务
+    pub transactions: bool,
+    /// 是否支持客户端 `bulkWrite`
+    pub bulk_write: bool,
+}
+
+impl WriteCapabilities {
+    /// 根据 `hello` 命令的返回值判断
+    pub fn from_hello(hello: &Document) -> Self {
+        let transactions = hello.contains_key("setName") || hello.get_str("msg") == Ok("isdbgrid");
+        let wire_version = match hello.get("maxWireVersion") {
+            Some(Bson::Int32(v)) => *v,
+            Some(Bson::Int64(v)) => *v as i32,
+            _ => 0,
+        };
+        Self {
+            transactions,
+            bulk_write: wire_version >= BULK_WRITE_MIN_WIRE_VERSION,
+        }
+    }
+}
+
+async fn write_capabilities(client: &Client) -> Result<WriteCapabilities> {
+    let hello = client
+        .database("admin")
+        .run_command(doc! { "hello": 1 })
+        .await?;
+    Ok(WriteCapabilities::from_hello(&hello))
+}
+
+/// 写入一次爬取的全部变更。
+///
+/// 在副本集上所有写入在同一个事务中完成，失败时整体回滚；
+/// 单机部署不支持事务，退回为依次写入。MongoDB 8.0 及以上使用一次 `bulkWrite` 提交，
+/// 更早的版本按集合分别批量写入。事务遇到临时错误时整体重试，提交结果未知时重试提交。
+pub async fn apply_arcade_writes(writes: &ArcadeWrites) -> Result<()> {
+    if writes.is_empty() {
+        return Ok(());
+    }
+
     let client = get_mongodb_client();
-    let collection: Collection<Arcade> = client.database(DB_NAME).collection("arcades");
+    let capabilities = write_capabilities(client).await?;
 
-    let filter = doc! { "arcade_id": arcade.arcade_id };
+    if !capabilities.transactions {
+        warn!("数据库不是副本集，无法使用事务，中途失败时可能只写入部分变更");
+        return write_arcade_changes(client, writes, capabilities, None).await;
+    }
 
-    // 使用整个文档进行替换，保留 _id 字段
-    let result = collection.replace_one(filter, arcade).await?;
+    let mut session = client.start_session().await?;
+    let started = Instant::now();
+    'transaction: loop {
+        session.start_transaction().await?;
+        if let Err(e) = write_arcade_changes(client, writes, capabilities, Some(&mut session)).await
+        {
+            if let Err(abort_error) = session.abort_transaction().await {
+                warn!("回滚事务失败：{}", abort_error);
+            }
+            if has_error_label(&e, TRANSIENT_TRANSACTION_ERROR)
+                && started.elapsed() < TRANSACTION_RETRY_TIMEOUT
+            {
+                warn!("写入机厅变更时遇到临时错误，重试事务：{}", e);
+                continue 'transaction;
+            }
+            return Err(e);
+        }
 
-    if result.matched_count == 0 {
-        return Err(anyhow::anyhow!(
-            "未找到要更新的机厅：ID {}",
-            arcade.arcade_id
-        ));
+        loop {
+            match session.commit_transaction().await {
+                Ok(()) => return Ok(()),
+                Err(e) if started.elapsed() >= TRANSACTION_RETRY_TIMEOUT => return Err(e.into()),
+                Err(e) if e.contains_label(UNKNOWN_TRANSACTION_COMMIT_RESULT) => {
+                    warn!("提交事务的结果未知，重试提交：{}", e);
+                }
+                Err(e) if e.contains_label(TRANSIENT_TRANSACTION_ERROR) => {
+                    warn!("提交事务时遇到临时错误，重试事务：{}", e);
+                    continue 'transaction;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
     }
+}
 
+fn has_error_label(e: &anyhow::Error, label: &str) -> bool {
+    e.downcast_ref::<mongodb::error::Error>()
+        .is_some_and(|e| e.contains_label(label))
+}
+
+/// 读取命令返回值中的整数，兼容 Int32 和 Int64
+fn get_integer(document: &Document, key: &str) -> Result<i64> {
+    match document.get(key) {
+        Some(Bson::Int32(v)) => Ok(*v as i64),
+        Some(Bson::Int64(v)) => Ok(*v),
+        _ => Err(anyhow::anyhow!("返回值中缺少整数字段 {}", key)),
+    }
+}
+
+async fn write_arcade_changes(
+    client: &Client,
+    writes: &ArcadeWrites,
+    capabilities: WriteCapabilities,
+    mut session: Option<&mut mongodb::ClientSession>,
+) -> Result<()> {
+    let database = client.database(DB_NAME);
+    let arcades: Collection<Arcade> = database.collection("arcades");
+    let events: Collection<ArcadeEvent> = database.collection("arcade_events");
+
+    if capabilities.bulk_write {
+        let mut models: Vec<mongodb::options::WriteModel> = Vec::new();
+        for arcade in &writes.replaced {
+            models.push(
+                arcades
+                    .replace_one_model(doc! { "arcade_id": arcade.arcade_id }, arcade)?
+                    .into(),
+            );
+        }
+        for arcade in &writes.inserted {
+            models.push(arcades.insert_one_model(arcade)?.into());
+        }
+        for event in &writes.events {
+            models.push(events.insert_one_model(event)?.into());
+        }
+
+        let action = client.bulk_write(models).ordered(true);
+        let result = match session.as_deref_mut() {
+            Some(session) => action.session(session).await?,
+            None => action.await?,
+        };
+        if result.matched_count < writes.replaced.len() as i64 {
+            return Err(anyhow::anyhow!(
+                "有 {} 个要更新的机厅未找到",
+                writes.replaced.len() as i64 - result.matched_count
+            ));
+        }
+        return Ok(());
+    }
+
+    // 每批替换合并为一条 update 命令，使用整个文档进行替换，保留 _id 字段
+    for batch in writes.replaced.chunks(REPLACE_BATCH_SIZE) {
+        let updates = batch
+            .iter()
+            .map(|arcade| {
+                Ok(doc! {
+                    "q": { "arcade_id": arcade.arcade_id },
+                    "u": mongodb::bson::to_document(arcade)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let action = database.run_command(doc! {
+            "update": arcades.name(),
+            "updates": updates,
+            "ordered": true,
+        });
+        let result = match session.as_deref_mut() {
+            Some(session) => action.session(session).await?,
+            None => action.await?,
+        };
+        if let Ok(errors) = result.get_array("writeErrors")
+            && let Some(Bson::Document(error)) = errors.first()
+        {
+            return Err(anyhow::anyhow!(
+                "替换机厅失败：{}",
+                error.get_str("errmsg").unwrap_or_default()
+            ));
+        }
+        let matched = get_integer(&result, "n")?;
+        if matched < batch.len() as i64 {
+            return Err(anyhow::anyhow!(
+                "有 {} 个要更新的机厅未找到",
+                batch.len() as i64 - matched
+            ));
+        }
+    }
+
+    if !writes.inserted.is_empty() {
+        let action = arcades.insert_many(&writes.inserted);
+        match session.as_deref_mut() {
+            Some(session) => action.session(session).await?,
+            None => action.await?,
+        };
+    }
+
+    if !writes.events.is_empty() {
+        let action = events.insert_many(&writes.events);
+        match session {
+            Some(session) => action.session(session).await?,
+            None => action.await?,
+        };
+    }
+
+    Ok(())
+}
+
+fn is_duplicate_key(e: &mongodb::error::Error) -> bool {
+    use mongodb::error::{ErrorKind, WriteFailure};
+
+    match e.kind.as_ref() {
+        ErrorKind::Write(WriteFailure::WriteError(e)) => e.code == 11000,
+        ErrorKind::Command(e) => e.code == 11000,
+        _ => false,
+    }
+}
+
+/// 当前进程作为锁持有者的标识，格式为 `<主机名>:<进程号>`
+pub fn lock_owner() -> String {
+    let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "unknown".to_string());
+    format!("{}:{}", host, std::process::id())
+}
+
+/// 尝试获取定时任务锁，成功时返回 `true`。
+///
+/// 同一任务的同一计划时间 `slot` 只会被一个实例执行；锁在 `ttl` 后自动失效，
+/// 避免实例崩溃后任务再也无法运行。
+pub async fn try_acquire_job_lock(
+    job: &str,
+    slot: DateTime,
+    owner: &str,
+    ttl: Duration,
+) -> Result<bool> {
+    let client = get_mongodb_client();
+    let collection: Collection<Document> = client.database(DB_NAME).collection("job_locks");
+
+    let now = DateTime::now();
+    let locked_until = DateTime::from_millis(now.timestamp_millis() + ttl.as_millis() as i64);
+    let filter = doc! { "_id": job, "locked_until": { "$lte": now }, "slot": { "$ne": slot } };
+    let update = doc! { "$set": { "slot": slot, "owner": owner, "locked_until": locked_until } };
+
+    // 锁被占用或该计划时间已执行过时，upsert 会因 _id 重复而失败
+    match collection.update_one(filter, update).upsert(true).await {
+        Ok(_) => Ok(true),
+        Err(e) if is_duplicate_key(&e) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// 续期仍由 `owner` 持有的任务锁，使其在 `ttl` 后才失效。
+///
+/// 返回 `false` 表示锁已不属于 `owner`（例如续期前已过期并被其他实例取得）。
+pub async fn renew_job_lock(job: &str, owner: &str, ttl: Duration) -> Result<bool> {
+    let client = get_mongodb_client();
+    let collection: Collection<Document> = client.database(DB_NAME).collection("job_locks");
+
+    let now = DateTime::now();
+    let locked_until = DateTime::from_millis(now.timestamp_millis() + ttl.as_millis() as i64);
+    let result = collection
+        .update_one(
+            doc! { "_id": job, "owner": owner, "locked_until": { "$gt": now } },
+            doc! { "$set": { "locked_until": locked_until } },
+        )
+        .await?;
+    Ok(result.matched_count > 0)
+}
+
+pub async fn release_job_lock(job: &str, owner: &str) -> Result<()> {
+    let client = get_mongodb_client();
+    let collection: Collection<Document> = client.database(DB_NAME).collection("job_locks");
+
+    collection
+        .update_one(
+            doc! { "_id": job, "owner": owner },
+            doc! { "$set": { "locked_until": DateTime::now() } },
+        )
+        .await?;
+    Ok(())
+}
+
+/// 运行 `task`，期间每隔 `period` 调用一次 `heartbeat`，返回 `task` 的结果
+pub async fn run_with_heartbeat<T, H, F>(
+    task: impl Future<Output = T>,
+    period: Duration,
+    mut heartbeat: H,
+) -> T
+where
+    H: FnMut() -> F,
+    F: Future<Output = ()>,
+{
+    tokio::pin!(task);
+    let mut interval = tokio::time::interval(period.max(Duration::from_secs(1)));
+    // 第一次 tick 立即完成，此时刚取得锁，无需续期
+    interval.tick().await;
+    loop {
+        tokio::select! {
+            result = &mut task => return result,
+            _ = interval.tick() => heartbeat().await,
+        }
+    }
+}
+
+/// 更新定时任务状态，`update` 中的字段会被 `$set` 到对应文档
+pub async fn update_job_status(job: &str, update: Document) -> Result<()> {
+    let client = get_mongodb_client();
+    let collection: Collection<JobStatus> = client.database(DB_NAME).collection("job_status");
+
+    collection
+        .update_one(doc! { "_id": job }, doc! { "$set": update })
+        .upsert(true)
+        .await?;
     Ok(())
 }
+
+pub async fn get_all_job_status() -> Result<Vec<JobStatus>> {
+    let client = get_mongodb_client();
+    let collection: Collection<JobStatus> = client.database(DB_NAME).collection("job_status");
+
+    let mut cursor = collection.find(doc! {}).sort(doc! { "_id": 1 }).await?;
+    let mut jobs = Vec::new();
+    while let Some(result) = cursor.next().await {
+        jobs.push(result?);
+    }
+
+    Ok(jobs)
+}
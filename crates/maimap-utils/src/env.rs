@@ -1,6 +1,6 @@
 use dotenvy::from_path;
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tracing::warn;
 
 fn find_env_file() -> Option<PathBuf> {
@@ -21,13 +21,18 @@ fn find_env_file() -> Option<PathBuf> {
 
     None
 }
+/// 加载指定的环境变量文件，已存在的环境变量不会被覆盖
+pub fn load_env_file(path: &Path) {
+    match from_path(path) {
+        Ok(_) => tracing::info!("已加载环境变量文件: {:?}", path),
+        Err(e) => warn!("无法加载环境变量文件 {:?}: {:?}", path, e),
+    }
+}
+
 pub fn check_required_env_vars() {
     // 查找并加载项目根目录的.env文件
     if let Some(env_path) = find_env_file() {
-        match from_path(&env_path) {
-            Ok(_) => tracing::info!("已加载环境变量文件: {:?}", env_path),
-            Err(e) => warn!("无法加载环境变量文件 {:?}: {:?}", env_path, e),
-        }
+        load_env_file(&env_path);
     } else {
         warn!("未找到.env文件");
     }
@@ -46,7 +51,7 @@ pub fn check_required_env_vars() {
     ];
 
     for var in required_vars {
-        if let Err(_) = env::var(var) {
+        if env::var(var).is_err() {
             warn!("缺少环境变量：{}", var);
         }
     }
@@ -90,4 +95,221 @@ pub fn aliyun_oss_endpoint() -> String {
 pub fn aliyun_oss_bucket_name() -> String {
     env::var("ALI_OSS_BUCKET_NAME").unwrap_or_else(|_| "".to_string())
 }
+
+/// 备份存储的类型：local、s3 或 oss，未设置时有阿里云凭据则使用 oss，否则使用 local
+pub fn backup_store() -> String {
+    env::var("BACKUP_STORE").unwrap_or_else(|_| {
+        if aliyun_acc_key_id().is_empty() {
+            "local".to_string()
+        } else {
+            "oss".to_string()
+        }
+    })
+}
+
+/// 备份格式：mongodump 或 native，native 不依赖 MongoDB Database Tools
+pub fn backup_format() -> String {
+    env::var("BACKUP_FORMAT").unwrap_or_else(|_| "mongodump".to_string())
+}
+
+/// 备份加密密钥，形如 `id1:<64位十六进制>,id2:<64位十六进制>`，轮换后旧密钥仍用于解密
+pub fn backup_encryption_keys() -> String {
+    env::var("BACKUP_ENCRYPTION_KEYS").unwrap_or_else(|_| "".to_string())
+}
+
+/// 加密新备份所用密钥的 ID，为空时不加密
+pub fn backup_encryption_key_id() -> String {
+    env::var("BACKUP_ENCRYPTION_KEY_ID").unwrap_or_else(|_| "".to_string())
+}
+
+/// 本地备份存储的目录，默认为备份目录下的 store 子目录
+pub fn backup_local_path() -> String {
+    env::var("BACKUP_LOCAL_PATH").unwrap_or_else(|_| {
+        std::path::Path::new(&backup_path())
+            .join("store")
+            .to_string_lossy()
+            .to_string()
+    })
+}
+
+/// 保留最近多少天的每日备份，与每周、每月备份均为 0 时不清理备份
+pub fn backup_keep_daily() -> usize {
+    env::var("BACKUP_KEEP_DAILY")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(7)
+}
+
+/// 保留最近多少周的每周备份
+pub fn backup_keep_weekly() -> usize {
+    env::var("BACKUP_KEEP_WEEKLY")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(4)
+}
+
+/// 保留最近多少个月的每月备份
+pub fn backup_keep_monthly() -> usize {
+    env::var("BACKUP_KEEP_MONTHLY")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(6)
+}
+
+pub fn s3_endpoint() -> String {
+    env::var("S3_ENDPOINT").unwrap_or_else(|_| "".to_string())
+}
+
+/// S3 签名使用的区域，MinIO 一般为 us-east-1，Cloudflare R2 为 auto
+pub fn s3_region() -> String {
+    env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string())
+}
+
+pub fn s3_bucket() -> String {
+    env::var("S3_BUCKET").unwrap_or_else(|_| "".to_string())
+}
+
+pub fn s3_access_key_id() -> String {
+    env::var("S3_ACCESS_KEY_ID").unwrap_or_else(|_| "".to_string())
+}
+
+pub fn s3_secret_access_key() -> String {
+    env::var("S3_SECRET_ACCESS_KEY").unwrap_or_else(|_| "".to_string())
+}
+
+/// 访问 /admin 接口所需的令牌，未设置时拒绝所有请求
+pub fn admin_token() -> String {
+    env::var("ADMIN_TOKEN").unwrap_or_else(|_| "".to_string())
+}
+
+/// 腾讯地图地址解析接口每秒允许的请求数
+pub fn geocoder_qps() -> f64 {
+    env::var("GEOCODER_QPS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(5.0)
+}
+
+/// 同时进行的地址解析请求数
+pub fn geocoder_concurrency() -> usize {
+    env::var("GEOCODER_CONCURRENCY")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(4)
+}
+
+/// 同一地址累计解析失败多少次后不再重试
+pub fn geocoder_max_attempts() -> i32 {
+    env::var("GEOCODER_MAX_ATTEMPTS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(5)
+}
+
+/// 累计失败次数达到上限的地址，距最近一次尝试多少小时后再重试一次
+pub fn geocoder_retry_after_hours() -> i64 {
+    env::var("GEOCODER_RETRY_AFTER_HOURS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(168)
+}
+
+/// 华立官网机厅列表页面所使用的接口地址
+pub fn wahlap_store_api_url() -> String {
+    env::var("WAHLAP_STORE_API_URL")
+        .unwrap_or_else(|_| "http://wc.wahlap.net/maidx/rest/location".to_string())
+}
+
+/// 机厅列表的数据来源：http 或 chrome
+pub fn scrape_store_source() -> String {
+    env::var("SCRAPE_STORE_SOURCE").unwrap_or_else(|_| "http".to_string())
+}
+
+/// 机厅列表至少应包含的机厅数量，低于此数量视为加载不完整
+pub fn scrape_min_arcades() -> usize {
+    env::var("SCRAPE_MIN_ARCADES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(2000)
+}
+
+/// 机厅列表页面的存档目录，为空时不存档
+pub fn scrape_html_archive_path() -> String {
+    env::var("SCRAPE_HTML_ARCHIVE_PATH").unwrap_or_else(|_| "".to_string())
+}
+
+/// 单次爬取允许标记关闭的机厅占存活机厅的最大比例
+pub fn scrape_max_closure_ratio() -> f64 {
+    env::var("SCRAPE_MAX_CLOSURE_RATIO")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0.05)
+}
+
+/// 单次爬取允许标记关闭的机厅最大数量
+pub fn scrape_max_closure_count() -> usize {
+    env::var("SCRAPE_MAX_CLOSURE_COUNT")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(100)
+}
+
+/// 是否允许超过阈值的大批量关闭
+pub fn scrape_allow_mass_closure() -> bool {
+    env::var("SCRAPE_ALLOW_MASS_CLOSURE")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// 不低于此置信度的疑似改名直接沿用原机厅ID
+pub fn scrape_rename_threshold() -> f64 {
+    env::var("SCRAPE_RENAME_THRESHOLD")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0.75)
+}
+
+/// 不低于此置信度的疑似改名会写入文件供人工确认
+pub fn scrape_rename_review_threshold() -> f64 {
+    env::var("SCRAPE_RENAME_REVIEW_THRESHOLD")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0.5)
+}
+
+/// 机厅连续多少次未出现在华立官网列表中才标记关闭，1 表示立即关闭
+pub fn scrape_closure_grace_runs() -> i32 {
+    env::var("SCRAPE_CLOSURE_GRACE_RUNS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(1)
+        .max(1)
+}
+
+/// 定时爬取任务的 cron 表达式（秒 分 时 日 月 周），为空时不运行
+pub fn schedule_scrape() -> String {
+    env::var("SCHEDULE_SCRAPE").unwrap_or_else(|_| "0 0 4 * * *".to_string())
+}
+
+/// 定时备份任务的 cron 表达式，为空时不运行
+pub fn schedule_backup() -> String {
+    env::var("SCHEDULE_BACKUP").unwrap_or_else(|_| "0 0 5 * * *".to_string())
+}
+
+/// 定时任务在计划时间后随机延迟的最大秒数
+pub fn scheduler_jitter_secs() -> u64 {
+    env::var("SCHEDULER_JITTER_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(300)
+}
+
+/// 定时任务锁的有效期（秒），任务运行期间会定期续期
+pub fn scheduler_lock_ttl_secs() -> u64 {
+    env::var("SCHEDULER_LOCK_TTL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(7200)
+}
+
 pub const DB_NAME: &str = "maimap";
@@ -21,6 +21,9 @@ pub enum AppError {
     #[error("OSS操作错误：{0}")]
     OssOperation(String),
 
+    #[error("备份存储错误：{0}")]
+    BackupStore(String),
+
     #[error("参数验证错误：{0}")]
     Validation(String),
 
@@ -41,4 +44,7 @@ pub enum AppError {
 
     #[error("调用腾讯地图API解析地址失败：{0}")]
     Geocoder(String),
+
+    #[error("数据库迁移失败：{0}")]
+    Migration(String),
 }
@@ -0,0 +1,274 @@
+use crate::db::{Bson, Database, Document, doc};
+use crate::errors::Result;
+use futures_util::stream::StreamExt;
+use mongodb::IndexModel;
+use mongodb::options::IndexOptions;
+use std::fmt::{Display, Formatter};
+use tracing::{info, warn};
+
+/// 代码中声明的索引
+pub struct IndexSpec {
+    pub collection: &'static str,
+    pub name: &'static str,
+    pub keys: Document,
+    pub unique: bool,
+}
+
+impl IndexSpec {
+    fn model(&self) -> IndexModel {
+        IndexModel::builder()
+            .keys(self.keys.clone())
+            .options(
+                IndexOptions::builder()
+                    .name(self.name.to_string())
+                    .unique(self.unique.then_some(true))
+                    .build(),
+            )
+            .build()
+    }
+}
+
+/// 服务与爬虫依赖的全部索引
+pub fn index_specs() -> Vec<IndexSpec> {
+    vec![
+        IndexSpec {
+            collection: "arcades",
+            name: "arcade_id_unique",
+            keys: doc! { "arcade_id": 1 },
+            unique: true,
+        },
+        // $geoNear 要求 arcade_pos 上有 2dsphere 索引
+        IndexSpec {
+            collection: "arcades",
+            name: "arcade_pos_2dsphere",
+            keys: doc! { "arcade_pos": "2dsphere" },
+            unique: false,
+        },
+        IndexSpec {
+            collection: "comments",
+            name: "arcade_id_created_at",
+            keys: doc! { "arcade_id": 1, "created_at": -1 },
+            unique: false,
+        },
+        IndexSpec {
+            collection: "tags",
+            name: "arcade_id_created_at",
+            keys: doc! { "arcade_id": 1, "created_at": -1 },
+            unique: false,
+        },
+        IndexSpec {
+            collection: "scrape_runs",
+            name: "started_at",
+            keys: doc! { "started_at": -1 },
+            unique: false,
+        },
+    ]
+}
+
+/// 数据库中的索引与代码中声明的不一致之处
+#[derive(Debug, PartialEq, Eq)]
+pub enum IndexDrift {
+    /// 声明了但数据库中不存在
+    Missing { collection: String, name: String },
+    /// 同名索引的字段或唯一性与声明不同
+    Mismatched {
+        collection: String,
+        name: String,
+        expected: String,
+        actual: String,
+    },
+    /// 数据库中存在但代码中未声明
+    Undeclared { collection: String, name: String },
+}
+
+impl IndexDrift {
+    /// 是否是 `specs` 中声明的唯一索引缺失或与声明不一致，此时数据库无法保证唯一性
+    pub fn breaks_uniqueness(&self, specs: &[IndexSpec]) -> bool {
+        let (IndexDrift::Missing { collection, name }
+        | IndexDrift::Mismatched {
+            collection, name, ..
+        }) = self
+        else {
+            return false;
+        };
+        specs
+            .iter()
+            .any(|spec| spec.collection == collection && spec.name == name && spec.unique)
+    }
+}
+
+impl Display for IndexDrift {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IndexDrift::Missing { collection, name } => {
+                write!(f, "{}.{} 缺失", collection, name)
+            }
+            IndexDrift::Mismatched {
+                collection,
+                name,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{}.{} 与声明不一致，期望 {}，实际 {}",
+                collection, name, expected, actual
+            ),
+            IndexDrift::Undeclared { collection, name } => {
+                write!(f, "{}.{} 未在代码中声明", collection, name)
+            }
+        }
+    }
+}
+
+/// 用于比较的索引描述，字段顺序有意义，数值类型不同但值相同时视为一致
+fn describe(keys: &Document, unique: bool) -> String {
+    let keys: Vec<String> = keys
+        .iter()
+        .map(|(field, value)| match value {
+            Bson::Int32(v) => format!("{}:{}", field, v),
+            Bson::Int64(v) => format!("{}:{}", field, v),
+            Bson::Double(v) => format!("{}:{}", field, v),
+            other => format!("{}:{}", field, other),
+        })
+        .collect();
+    format!(
+        "{{{}}}{}",
+        keys.join(", "),
+        if unique { " unique" } else { "" }
+    )
+}
+
+fn index_name(index: &IndexModel) -> Option<&str> {
+    index
+        .options
+        .as_ref()
+        .and_then(|options| options.name.as_deref())
+}
+
+fn describe_index(index: &IndexModel) -> String {
+    let unique = index
+        .options
+        .as_ref()
+        .and_then(|options| options.unique)
+        .unwrap_or(false);
+    describe(&index.keys, unique)
+}
+
+async fn existing_indexes(db: &Database, collection: &str) -> Result<Vec<IndexModel>> {
+    let mut cursor = match db.collection::<Document>(collection).list_indexes().await {
+        Ok(cursor) => cursor,
+        // 集合尚不存在
+        Err(e) if matches!(e.kind.as_ref(), mongodb::error::ErrorKind::Command(e) if e.code == 26) =>
+        {
+            return Ok(Vec::new());
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut indexes = Vec::new();
+    while let Some(index) = cursor.next().await {
+        indexes.push(index?);
+    }
+    Ok(indexes)
+}
+
+/// 比较集合 `collection` 中已有的索引与 `specs` 中对该集合的声明。
+///
+/// 声明的索引先按名称匹配，找不到时字段与唯一性相同的索引也视为已满足声明；
+/// 除 `_id_` 外没有对应声明的已有索引报告为未声明。
+pub fn compare_indexes(
+    collection: &str,
+    specs: &[IndexSpec],
+    existing: &[IndexModel],
+) -> Vec<IndexDrift> {
+    let mut drifts = Vec::new();
+    let mut matched = Vec::new();
+    for spec in specs.iter().filter(|spec| spec.collection == collection) {
+        let expected = describe(&spec.keys, spec.unique);
+        let found = existing
+            .iter()
+            .find(|index| index_name(index) == Some(spec.name))
+            .or_else(|| {
+                existing
+                    .iter()
+                    .find(|index| describe_index(index) == expected)
+            });
+        match found {
+            None => drifts.push(IndexDrift::Missing {
+                collection: collection.to_string(),
+                name: spec.name.to_string(),
+            }),
+            Some(index) => {
+                matched.extend(index_name(index));
+                let actual = describe_index(index);
+                if expected != actual {
+                    drifts.push(IndexDrift::Mismatched {
+                        collection: collection.to_string(),
+                        name: spec.name.to_string(),
+                        expected,
+                        actual,
+                    });
+                }
+            }
+        }
+    }
+
+    for name in existing.iter().filter_map(index_name) {
+        if name != "_id_" && !matched.contains(&name) {
+            drifts.push(IndexDrift::Undeclared {
+                collection: collection.to_string(),
+                name: name.to_string(),
+            });
+        }
+    }
+
+    drifts
+}
+
+/// 比较数据库中的索引与 `specs`，不做任何修改
+pub async fn check_indexes(db: &Database, specs: &[IndexSpec]) -> Result<Vec<IndexDrift>> {
+    let mut collections: Vec<&str> = specs.iter().map(|spec| spec.collection).collect();
+    collections.sort();
+    collections.dedup();
+
+    let mut drifts = Vec::new();
+    for collection in collections {
+        let existing = existing_indexes(db, collection).await?;
+        drifts.extend(compare_indexes(collection, specs, &existing));
+    }
+
+    Ok(drifts)
+}
+
+/// 创建缺失的索引，并以警告报告其余不一致之处。
+///
+/// 不一致或未声明的索引不会被自动删除，需要人工确认后处理。
+/// 返回创建索引后仍存在的不一致之处，创建失败（如已有数据违反唯一约束）的索引仍报告为缺失。
+pub async fn ensure_indexes(db: &Database, specs: &[IndexSpec]) -> Result<Vec<IndexDrift>> {
+    let mut remaining = Vec::new();
+    for drift in check_indexes(db, specs).await? {
+        let IndexDrift::Missing { collection, name } = &drift else {
+            warn!("索引{}", drift);
+            remaining.push(drift);
+            continue;
+        };
+        let Some(spec) = specs
+            .iter()
+            .find(|spec| spec.collection == collection && spec.name == name)
+        else {
+            continue;
+        };
+
+        info!("创建索引 {}.{}", collection, name);
+        if let Err(e) = db
+            .collection::<Document>(collection)
+            .create_index(spec.model())
+            .await
+        {
+            warn!("创建索引 {}.{} 失败：{}", collection, name, e);
+            remaining.push(drift);
+        }
+    }
+
+    Ok(remaining)
+}
@@ -1,3 +1,10 @@
+// 使派生宏生成的 `::maimap_utils::...` 路径在本 crate 内同样可用
+extern crate self as maimap_utils;
+
+// 派生宏生成的代码经由这里使用 serde，使用派生宏的 crate 不必自行依赖
+pub use serde;
+pub use serde_json;
+
 pub mod backup;
 pub mod db;
 
@@ -5,5 +12,9 @@ pub mod env;
 
 pub mod errors;
 
+pub mod indexes;
+
+pub mod migrations;
+
 pub mod traits;
 pub mod types;
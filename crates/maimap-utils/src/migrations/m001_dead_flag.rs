@@ -0,0 +1,37 @@
+use super::Migration;
+use crate::db::{Bson, Collection, Database, Document, doc};
+use crate::errors::Result;
+use async_trait::async_trait;
+use tracing::info;
+
+/// 将为 null 的 arcade_dead 转换为 false
+pub(super) struct DeadFlag;
+
+#[async_trait]
+impl Migration for DeadFlag {
+    fn version(&self) -> i64 {
+        1
+    }
+
+    fn name(&self) -> &str {
+        "dead_flag_null_to_false"
+    }
+
+    async fn up(&self, db: &Database) -> Result<()> {
+        let collection: Collection<Document> = db.collection("arcades");
+
+        // 筛选出 arcade_dead 字段为 null 的文档，将其设置为 false
+        let update_result = collection
+            .update_many(
+                doc! { "arcade_dead": Bson::Null },
+                doc! { "$set": { "arcade_dead": false } },
+            )
+            .await?;
+
+        info!(
+            "arcade_dead 数据转换完成。总共更新了 {} 个文档。",
+            update_result.modified_count
+        );
+        Ok(())
+    }
+}
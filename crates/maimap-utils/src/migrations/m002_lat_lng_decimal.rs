@@ -0,0 +1,77 @@
+use super::Migration;
+use crate::db::{Bson, Collection, Database, Decimal128, Document, doc};
+use crate::errors::{AppError, Result};
+use async_trait::async_trait;
+use futures_util::stream::StreamExt;
+use std::str::FromStr;
+use tracing::info;
+
+/// 将 Double 或 String 类型的经纬度转换为 Decimal128
+pub(super) struct LatLngDecimal;
+
+fn to_decimal(value: &Bson) -> Result<Option<Decimal128>> {
+    let decimal = match value {
+        Bson::Double(value) => Some(value.to_string()),
+        Bson::String(value) if !value.is_empty() => Some(value.clone()),
+        _ => None,
+    };
+    decimal
+        .map(|value| {
+            Decimal128::from_str(&value).map_err(|e| AppError::Parse(e.to_string()).into())
+        })
+        .transpose()
+}
+
+#[async_trait]
+impl Migration for LatLngDecimal {
+    fn version(&self) -> i64 {
+        2
+    }
+
+    fn name(&self) -> &str {
+        "lat_lng_to_decimal128"
+    }
+
+    async fn up(&self, db: &Database) -> Result<()> {
+        let collection: Collection<Document> = db.collection("arcades");
+
+        // 筛选出 arcade_lat 或 arcade_lng 字段为 Double 或 String 类型的文档
+        let filter = doc! {
+            "$or": [
+                { "arcade_lat": { "$type": "double" } },
+                { "arcade_lng": { "$type": "double" } },
+                { "arcade_lat": { "$type": "string" } },
+                { "arcade_lng": { "$type": "string" } }
+            ]
+        };
+
+        let mut cursor = collection.find(filter).await?;
+        let mut total_updated = 0;
+
+        while let Some(result) = cursor.next().await {
+            let doc = result?;
+            let id = doc
+                .get_object_id("_id")
+                .map_err(|e| AppError::Parse(e.to_string()))?;
+
+            let mut updates = Document::new();
+            for field in ["arcade_lat", "arcade_lng"] {
+                if let Some(value) = doc.get(field)
+                    && let Some(decimal) = to_decimal(value)?
+                {
+                    updates.insert(field, decimal);
+                }
+            }
+
+            if !updates.is_empty() {
+                let update_result = collection
+                    .update_one(doc! { "_id": id }, doc! { "$set": updates })
+                    .await?;
+                total_updated += update_result.modified_count;
+            }
+        }
+
+        info!("经纬度类型转换完成。总共更新了 {} 个文档。", total_updated);
+        Ok(())
+    }
+}
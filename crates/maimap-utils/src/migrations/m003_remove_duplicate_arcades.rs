@@ -0,0 +1,84 @@
+use super::Migration;
+use crate::db::{Bson, Collection, Database, Document, doc};
+use crate::errors::{AppError, Result};
+use async_trait::async_trait;
+use futures_util::stream::StreamExt;
+use tracing::info;
+
+/// 删除 arcade_id 与名称都相同的重复机厅，只保留第一条
+pub(super) struct RemoveDuplicateArcades;
+
+#[async_trait]
+impl Migration for RemoveDuplicateArcades {
+    fn version(&self) -> i64 {
+        3
+    }
+
+    fn name(&self) -> &str {
+        "remove_duplicate_arcades"
+    }
+
+    async fn up(&self, db: &Database) -> Result<()> {
+        let collection: Collection<Document> = db.collection("arcades");
+
+        // 使用聚合管道查找具有相同arcade_id和arcade_name的记录
+        let pipeline = vec![
+            doc! {
+                "$group": {
+                    "_id": {
+                        "arcade_id": "$arcade_id",
+                        "arcade_name": "$arcade_name"
+                    },
+                    "ids": { "$push": "$_id" },
+                    "count": { "$sum": 1 }
+                }
+            },
+            doc! {
+                "$match": {
+                    "count": { "$gt": 1 }
+                }
+            },
+        ];
+
+        let mut cursor = collection.aggregate(pipeline).await?;
+        let mut total_deleted = 0;
+
+        while let Some(result) = cursor.next().await {
+            let group = result?;
+            let ids = group
+                .get_array("ids")
+                .map_err(|e| AppError::Parse(e.to_string()))?;
+
+            // 保留第一个文档，删除其余的
+            let ids_to_delete: Vec<_> = ids
+                .iter()
+                .skip(1)
+                .filter_map(|id| match id {
+                    Bson::ObjectId(oid) => Some(*oid),
+                    _ => None,
+                })
+                .collect();
+            if ids_to_delete.is_empty() {
+                continue;
+            }
+
+            let delete_result = collection
+                .delete_many(doc! { "_id": { "$in": ids_to_delete } })
+                .await?;
+            total_deleted += delete_result.deleted_count;
+
+            let id_doc = group
+                .get_document("_id")
+                .map_err(|e| AppError::Parse(e.to_string()))?;
+            info!(
+                "删除了 {} 个重复的机厅记录: arcade_id={}, arcade_name={}",
+                delete_result.deleted_count,
+                id_doc.get("arcade_id").unwrap_or(&Bson::Null),
+                id_doc.get("arcade_name").unwrap_or(&Bson::Null)
+            );
+        }
+
+        info!("总共删除了 {} 个重复的机厅记录", total_deleted);
+        Ok(())
+    }
+}
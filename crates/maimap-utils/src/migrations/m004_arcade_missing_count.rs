@@ -0,0 +1,37 @@
+use super::Migration;
+use crate::db::{Collection, Database, Document, doc};
+use crate::errors::Result;
+use async_trait::async_trait;
+
+/// 为已有机厅补充连续缺失次数字段
+pub(super) struct ArcadeMissingCount;
+
+#[async_trait]
+impl Migration for ArcadeMissingCount {
+    fn version(&self) -> i64 {
+        4
+    }
+
+    fn name(&self) -> &str {
+        "add_arcade_missing_count"
+    }
+
+    async fn up(&self, db: &Database) -> Result<()> {
+        let collection: Collection<Document> = db.collection("arcades");
+        collection
+            .update_many(
+                doc! { "arcade_missing_count": { "$exists": false } },
+                doc! { "$set": { "arcade_missing_count": 0 } },
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, db: &Database) -> Result<()> {
+        let collection: Collection<Document> = db.collection("arcades");
+        collection
+            .update_many(doc! {}, doc! { "$unset": { "arcade_missing_count": "" } })
+            .await?;
+        Ok(())
+    }
+}
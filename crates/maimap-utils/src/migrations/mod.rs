@@ -0,0 +1,266 @@
+mod m001_dead_flag;
+mod m002_lat_lng_decimal;
+mod m003_remove_duplicate_arcades;
+mod m004_arcade_missing_count;
+
+use crate::db::{
+    Collection, Database, DateTime, doc, get_mongodb_client, lock_owner, release_job_lock,
+    renew_job_lock, run_with_heartbeat, try_acquire_job_lock,
+};
+use crate::env::{DB_NAME, scheduler_lock_ttl_secs};
+use crate::errors::{AppError, Result};
+use async_trait::async_trait;
+use futures_util::stream::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// 一次有版本号的数据库变更，每个版本只会执行一次
+#[async_trait]
+pub trait Migration: Send + Sync {
+    /// 递增的版本号，决定执行顺序
+    fn version(&self) -> i64;
+
+    /// 简短的描述，用于日志与 `migrate status`
+    fn name(&self) -> &str;
+
+    async fn up(&self, db: &Database) -> Result<()>;
+
+    /// 回滚此次变更，默认不支持
+    async fn down(&self, _db: &Database) -> Result<()> {
+        Err(AppError::Migration(format!(
+            "迁移 {} {} 不支持回滚",
+            self.version(),
+            self.name()
+        ))
+        .into())
+    }
+}
+
+/// 按版本号排列的全部迁移
+pub fn all_migrations() -> Vec<Box<dyn Migration>> {
+    vec![
+        Box::new(m001_dead_flag::DeadFlag),
+        Box::new(m002_lat_lng_decimal::LatLngDecimal),
+        Box::new(m003_remove_duplicate_arcades::RemoveDuplicateArcades),
+        Box::new(m004_arcade_missing_count::ArcadeMissingCount),
+    ]
+}
+
+/// `schema_migrations` 集合中已执行迁移的记录
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AppliedMigration {
+    #[serde(rename = "_id")]
+    pub version: i64,
+    pub name: String,
+    pub applied_at: DateTime,
+}
+
+/// 迁移及其执行情况
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: String,
+    /// 未执行时为 `None`
+    pub applied_at: Option<DateTime>,
+}
+
+/// 需要执行的迁移：未执行过且版本号不超过 `target`，按版本号从低到高排列
+pub fn pending_versions(versions: &[i64], applied: &HashSet<i64>, target: Option<i64>) -> Vec<i64> {
+    let mut pending: Vec<i64> = versions
+        .iter()
+        .copied()
+        .filter(|version| !applied.contains(version))
+        .filter(|version| target.is_none_or(|target| *version <= target))
+        .collect();
+    pending.sort_unstable();
+    pending
+}
+
+/// 需要回滚的迁移，按版本号从高到低排列。
+///
+/// 指定 `target` 时为所有版本号大于它的已执行迁移，否则只有最近的一个。
+pub fn rollback_versions(
+    versions: &[i64],
+    applied: &HashSet<i64>,
+    target: Option<i64>,
+) -> Vec<i64> {
+    let mut executed: Vec<i64> = versions
+        .iter()
+        .copied()
+        .filter(|version| applied.contains(version))
+        .collect();
+    executed.sort_unstable_by(|a, b| b.cmp(a));
+    match target {
+        Some(target) => executed.retain(|version| *version > target),
+        None => executed.truncate(1),
+    }
+    executed
+}
+
+pub struct Migrator {
+    db: Database,
+    migrations: Vec<Box<dyn Migration>>,
+    owner: String,
+}
+
+impl Migrator {
+    pub fn new(db: Database, mut migrations: Vec<Box<dyn Migration>>) -> Self {
+        migrations.sort_by_key(|migration| migration.version());
+        Self {
+            db,
+            migrations,
+            owner: lock_owner(),
+        }
+    }
+
+    /// 使用已连接的数据库与全部迁移
+    pub fn from_client() -> Self {
+        Self::new(get_mongodb_client().database(DB_NAME), all_migrations())
+    }
+
+    fn collection(&self) -> Collection<AppliedMigration> {
+        self.db.collection("schema_migrations")
+    }
+
+    async fn applied(&self) -> Result<HashMap<i64, AppliedMigration>> {
+        let mut cursor = self.collection().find(doc! {}).await?;
+        let mut applied = HashMap::new();
+        while let Some(result) = cursor.next().await {
+            let migration = result?;
+            applied.insert(migration.version, migration);
+        }
+        Ok(applied)
+    }
+
+    async fn applied_versions(&self) -> Result<HashSet<i64>> {
+        Ok(self.applied().await?.into_keys().collect())
+    }
+
+    fn versions(&self) -> Vec<i64> {
+        self.migrations
+            .iter()
+            .map(|migration| migration.version())
+            .collect()
+    }
+
+    /// 同一数据库的迁移锁名，`job_locks` 中与定时任务的锁共用
+    fn lock_name(&self) -> String {
+        format!("migrations:{}", self.db.name())
+    }
+
+    /// 持有迁移锁运行 `task`，避免 daemon 启动时与单独执行的 `migrate` 同时修改数据库。
+    ///
+    /// 运行期间定期续期，迁移时间超过锁的有效期时也不会被其他进程取得。
+    async fn with_lock<T>(&self, task: impl Future<Output = Result<T>>) -> Result<T> {
+        let ttl = Duration::from_secs(scheduler_lock_ttl_secs());
+        if !try_acquire_job_lock(&self.lock_name(), DateTime::now(), &self.owner, ttl).await? {
+            return Err(
+                AppError::Migration("其他进程正在执行数据库迁移，请稍后重试".to_string()).into(),
+            );
+        }
+
+        let result = run_with_heartbeat(task, ttl / 3, || self.renew_lock(ttl)).await;
+        let unlocked = release_job_lock(&self.lock_name(), &self.owner).await;
+        let value = result?;
+        unlocked?;
+        Ok(value)
+    }
+
+    /// 续期失败时迁移继续执行，只记录日志
+    async fn renew_lock(&self, ttl: Duration) {
+        match renew_job_lock(&self.lock_name(), &self.owner, ttl).await {
+            Ok(true) => {}
+            Ok(false) => warn!("迁移锁已失效，其他进程可能同时执行迁移"),
+            Err(e) => warn!("续期迁移锁失败：{:#}", e),
+        }
+    }
+
+    pub async fn status(&self) -> Result<Vec<MigrationStatus>> {
+        let applied = self.applied().await?;
+        Ok(self
+            .migrations
+            .iter()
+            .map(|migration| MigrationStatus {
+                version: migration.version(),
+                name: migration.name().to_string(),
+                applied_at: applied
+                    .get(&migration.version())
+                    .map(|applied| applied.applied_at),
+            })
+            .collect())
+    }
+
+    /// 依次执行未执行过、且版本号不超过 `target` 的迁移，返回执行（dry-run 时为将要执行）的版本号。
+    ///
+    /// 非 dry-run 时持有迁移锁，其他进程正在迁移时返回错误。
+    pub async fn up(&self, target: Option<i64>, dry_run: bool) -> Result<Vec<i64>> {
+        if dry_run {
+            return self.apply(target, true).await;
+        }
+        self.with_lock(self.apply(target, false)).await
+    }
+
+    async fn apply(&self, target: Option<i64>, dry_run: bool) -> Result<Vec<i64>> {
+        let applied = self.applied_versions().await?;
+        let versions = pending_versions(&self.versions(), &applied, target);
+
+        for migration in &self.migrations {
+            let version = migration.version();
+            if !versions.contains(&version) {
+                continue;
+            }
+
+            if dry_run {
+                info!("[dry-run] 将执行迁移 {} {}", version, migration.name());
+            } else {
+                info!("执行迁移 {} {}", version, migration.name());
+                migration.up(&self.db).await?;
+                self.collection()
+                    .insert_one(AppliedMigration {
+                        version,
+                        name: migration.name().to_string(),
+                        applied_at: DateTime::now(),
+                    })
+                    .await?;
+            }
+        }
+
+        Ok(versions)
+    }
+
+    /// 按版本号从高到低回滚已执行的迁移。
+    ///
+    /// 指定 `target` 时回滚所有版本号大于它的迁移，否则只回滚最近的一个；非 dry-run 时持有迁移锁。
+    pub async fn down(&self, target: Option<i64>, dry_run: bool) -> Result<Vec<i64>> {
+        if dry_run {
+            return self.rollback(target, true).await;
+        }
+        self.with_lock(self.rollback(target, false)).await
+    }
+
+    async fn rollback(&self, target: Option<i64>, dry_run: bool) -> Result<Vec<i64>> {
+        let applied = self.applied_versions().await?;
+        let versions = rollback_versions(&self.versions(), &applied, target);
+
+        for migration in self.migrations.iter().rev() {
+            let version = migration.version();
+            if !versions.contains(&version) {
+                continue;
+            }
+
+            if dry_run {
+                info!("[dry-run] 将回滚迁移 {} {}", version, migration.name());
+            } else {
+                info!("回滚迁移 {} {}", version, migration.name());
+                migration.down(&self.db).await?;
+                self.collection()
+                    .delete_one(doc! { "_id": version })
+                    .await?;
+            }
+        }
+
+        Ok(versions)
+    }
+}
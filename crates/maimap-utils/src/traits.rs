@@ -1,11 +1,157 @@
+use mongodb::bson::oid::ObjectId;
+use mongodb::bson::{DateTime, Decimal128};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
 use serde_json::Value;
 
 pub trait ToResponse {
-    fn to_response(&self) -> Value;
+    /// 响应的类型，`#[derive(ToResponse)]` 会生成名为 `<类型名>Response` 的结构体或枚举
+    type Response: Serialize + DeserializeOwned;
+
+    fn to_typed_response(&self) -> Self::Response;
+
+    fn to_response(&self) -> Value {
+        serde_json::to_value(self.to_typed_response()).unwrap_or(Value::Null)
+    }
 }
 
 impl<T: ToResponse> ToResponse for Vec<T> {
-    fn to_response(&self) -> Value {
-        Value::Array(self.iter().map(|item| item.to_response()).collect())
+    type Response = Vec<T::Response>;
+
+    fn to_typed_response(&self) -> Self::Response {
+        self.iter().map(ToResponse::to_typed_response).collect()
+    }
+}
+
+/// 可以作为响应中一个字段输出的类型。
+///
+/// `#[derive(ToResponse)]` 对每个字段调用此 trait，派生了 `ToResponse` 的类型也会实现它，
+/// 因此 `Option`、`Vec` 与嵌套的结构体、枚举都按同样的规则转换，不会输出 BSON 的扩展 JSON。
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` 不能作为响应字段输出",
+    label = "未实现 `ResponseField`",
+    note = "可以为该类型派生 `ToResponse`，或在字段上使用 `#[response(with = ...)]` 或 `#[DoNotRespond]`"
+)]
+pub trait ResponseField {
+    /// 字段在响应中的类型
+    type Response: Serialize + DeserializeOwned;
+
+    fn to_response_field(&self) -> Self::Response;
+}
+
+/// 派生宏生成的代码通过此 trait 转换字段，并在实现上以它约束各字段的类型。
+///
+/// 所有实现了 `ResponseField` 的类型都实现它；字段类型未实现时只在该字段的类型上报告一条错误，
+/// 不会牵连生成的响应类型及其派生的 trait。不应直接使用。
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` 不能作为响应字段输出",
+    label = "未实现 `ResponseField`",
+    note = "可以为该类型派生 `ToResponse`，或在字段上使用 `#[response(with = ...)]` 或 `#[DoNotRespond]`"
+)]
+pub trait DerivedField {
+    type Response: Serialize + DeserializeOwned;
+
+    fn derived_field(&self) -> Self::Response;
+}
+
+#[diagnostic::do_not_recommend]
+impl<T: ResponseField + ?Sized> DerivedField for T {
+    type Response = T::Response;
+
+    fn derived_field(&self) -> T::Response {
+        self.to_response_field()
     }
 }
+
+/// 与 `DerivedField` 相同，用于 `#[response(flatten)]` 的字段。不应直接使用。
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` 不能展开到响应中",
+    label = "未实现 `ToResponse`",
+    note = "`#[response(flatten)]` 的字段需要派生 `ToResponse`"
+)]
+pub trait DerivedFlatten {
+    type Response: Serialize + DeserializeOwned;
+
+    fn derived_flatten(&self) -> Self::Response;
+}
+
+#[diagnostic::do_not_recommend]
+impl<T: ToResponse + ?Sized> DerivedFlatten for T {
+    type Response = T::Response;
+
+    fn derived_flatten(&self) -> T::Response {
+        self.to_typed_response()
+    }
+}
+
+/// 输出带毫秒的 UTC 时间，如 `2025-04-14T12:06:00.000Z`，
+/// 与此前机厅搜索中 `$dateToString` 的 `%Y-%m-%dT%H:%M:%S.%LZ` 格式一致
+impl ResponseField for DateTime {
+    type Response = String;
+
+    fn to_response_field(&self) -> String {
+        chrono::DateTime::from_timestamp_millis(self.timestamp_millis())
+            .map(|time| time.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// 输出十六进制字符串
+impl ResponseField for ObjectId {
+    type Response = String;
+
+    fn to_response_field(&self) -> String {
+        self.to_hex()
+    }
+}
+
+/// 输出浮点数
+impl ResponseField for Decimal128 {
+    type Response = f64;
+
+    fn to_response_field(&self) -> f64 {
+        self.to_string().parse().unwrap_or(0.0)
+    }
+}
+
+impl<T: ResponseField> ResponseField for Option<T> {
+    type Response = Option<T::Response>;
+
+    fn to_response_field(&self) -> Self::Response {
+        self.as_ref().map(ResponseField::to_response_field)
+    }
+}
+
+impl<T: ResponseField> ResponseField for Vec<T> {
+    type Response = Vec<T::Response>;
+
+    fn to_response_field(&self) -> Self::Response {
+        self.iter().map(ResponseField::to_response_field).collect()
+    }
+}
+
+impl<T: ResponseField> ResponseField for Box<T> {
+    type Response = T::Response;
+
+    fn to_response_field(&self) -> Self::Response {
+        (**self).to_response_field()
+    }
+}
+
+macro_rules! impl_response_field {
+    ($($ty:ty),*) => {
+        $(
+            impl ResponseField for $ty {
+                type Response = $ty;
+
+                fn to_response_field(&self) -> $ty {
+                    self.clone()
+                }
+            }
+        )*
+    };
+}
+
+impl_response_field!(bool, i32, i64, u32, u64, usize, f32, f64, String, Value);
@@ -1,4 +1,3 @@
-use crate::traits::ToResponse;
 use maimap_derive::ToResponse;
 use mongodb::bson::oid::ObjectId;
 use mongodb::bson::{DateTime, Decimal128};
@@ -25,7 +24,7 @@ impl Point {
     }
 }
 
-#[derive(Deserialize, Serialize, ToResponse)]
+#[derive(Clone, Deserialize, Serialize, ToResponse)]
 pub struct Arcade {
     /// 机厅地址
     pub arcade_address: String,
@@ -47,10 +46,222 @@ pub struct Arcade {
 
     /// 机厅名
     pub arcade_name: String,
+    /// 华立店铺ID
+    #[serde(default)]
+    pub arcade_store_id: Option<String>,
+    /// 所在省份
+    #[serde(default)]
+    pub arcade_province: Option<String>,
+    /// 机台信息，爬取自官网接口的机台数量
+    #[serde(default)]
+    pub arcade_machine: Option<String>,
+    /// 连续未出现在华立官网机厅列表中的爬取次数
+    #[DoNotRespond]
+    #[serde(default)]
+    pub arcade_missing_count: i32,
     /// 创建时间
     pub created_at: DateTime,
 }
 
+/// 机厅搜索结果
+#[derive(Clone, Deserialize, ToResponse)]
+pub struct ArcadeSearchResult {
+    #[serde(flatten)]
+    #[response(flatten)]
+    pub arcade: Arcade,
+    /// 与搜索位置的距离（米），仅地理位置搜索时有值
+    #[response(skip_if = "Option::is_none")]
+    pub distance: Option<f64>,
+}
+
+/// 机厅状态变化的类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArcadeEventKind {
+    /// 机厅从华立官网消失，被标记关闭
+    Closed,
+    /// 已关闭的机厅重新出现在华立官网
+    Reopened,
+}
+
+/// 机厅状态变化记录
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ArcadeEvent {
+    /// 机厅ID
+    pub arcade_id: i32,
+    /// 事件类型
+    pub event: ArcadeEventKind,
+    /// 发生事件时的机厅名
+    pub arcade_name: String,
+    /// 发生事件时的机厅地址
+    pub arcade_address: String,
+    /// 记录时间
+    pub created_at: DateTime,
+}
+
+impl ArcadeEvent {
+    pub fn new(arcade: &Arcade, event: ArcadeEventKind, created_at: DateTime) -> Self {
+        Self {
+            arcade_id: arcade.arcade_id,
+            event,
+            arcade_name: arcade.arcade_name.clone(),
+            arcade_address: arcade.arcade_address.clone(),
+            created_at,
+        }
+    }
+}
+
+/// 爬虫运行结果
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, ToResponse)]
+#[serde(rename_all = "snake_case")]
+pub enum ScrapeRunStatus {
+    Succeeded,
+    Failed,
+}
+
+/// 置信度不足以自动处理、需要人工确认的疑似改名
+#[derive(Clone, Debug, Deserialize, Serialize, ToResponse)]
+pub struct RenameReview {
+    /// 数据库中待关闭机厅的ID
+    pub arcade_id: i32,
+    /// 数据库中的机厅名
+    pub old_name: String,
+    /// 数据库中的地址
+    pub old_address: String,
+    /// 网站上新出现的机厅名
+    pub new_name: String,
+    /// 网站上新出现的地址
+    pub new_address: String,
+    /// 0 到 1 之间的置信度
+    pub confidence: f64,
+}
+
+/// 一次爬虫运行的记录
+#[derive(Clone, Deserialize, Serialize, ToResponse)]
+pub struct ScrapeRun {
+    /// 记录ID
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    /// 开始时间
+    pub started_at: DateTime,
+    /// 结束时间
+    pub finished_at: DateTime,
+    /// 运行结果
+    pub status: ScrapeRunStatus,
+    /// 来源页面中的机厅条目数
+    pub page_arcade_count: i32,
+    /// 去重后解析出的机厅数
+    pub parsed_arcade_count: i32,
+    /// 新增的机厅ID
+    pub new_arcade_ids: Vec<i32>,
+    /// 信息更新的机厅ID
+    pub updated_arcade_ids: Vec<i32>,
+    /// 重新开放的机厅ID
+    pub reopened_arcade_ids: Vec<i32>,
+    /// 标记关闭的机厅ID
+    pub closed_arcade_ids: Vec<i32>,
+    /// 向腾讯地图地址解析接口发出的请求数，包括重试
+    pub geocoder_calls: i32,
+    /// 需要人工确认的疑似改名
+    #[serde(default)]
+    pub rename_reviews: Vec<RenameReview>,
+    /// 待关闭数量超过阈值时的候选机厅ID
+    #[serde(default)]
+    pub closure_candidate_ids: Vec<i32>,
+    /// 运行中出现的错误
+    pub errors: Vec<String>,
+}
+
+impl ScrapeRun {
+    pub fn start() -> Self {
+        let now = DateTime::now();
+        Self {
+            id: ObjectId::new(),
+            started_at: now,
+            finished_at: now,
+            status: ScrapeRunStatus::Succeeded,
+            page_arcade_count: 0,
+            parsed_arcade_count: 0,
+            new_arcade_ids: Vec::new(),
+            updated_arcade_ids: Vec::new(),
+            reopened_arcade_ids: Vec::new(),
+            closed_arcade_ids: Vec::new(),
+            geocoder_calls: 0,
+            rename_reviews: Vec::new(),
+            closure_candidate_ids: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// 记录结束时间与运行结果
+    pub fn finish(&mut self, error: Option<String>) {
+        self.finished_at = DateTime::now();
+        if let Some(error) = error {
+            self.status = ScrapeRunStatus::Failed;
+            self.errors.push(error);
+        }
+    }
+}
+
+/// 已解析过的地址，爬虫中断后重新运行时无需再次调用地图接口
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GeocodeCacheEntry {
+    /// 机厅地址
+    #[serde(rename = "_id")]
+    pub address: String,
+    pub lat: f64,
+    pub lng: f64,
+    /// 解析时间
+    pub updated_at: DateTime,
+}
+
+/// 解析失败、等待下次爬取时重试的地址
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GeocodeFailure {
+    /// 机厅地址
+    #[serde(rename = "_id")]
+    pub address: String,
+    /// 机厅名
+    pub arcade_name: String,
+    /// 最近一次失败的原因
+    pub error: String,
+    /// 累计失败次数
+    pub attempts: i32,
+    /// 最近一次尝试的时间
+    pub last_attempt_at: DateTime,
+}
+
+/// 定时任务的运行结果
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, ToResponse)]
+#[serde(rename_all = "snake_case")]
+pub enum JobRunStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// 定时任务的最近一次与下一次运行情况
+#[derive(Clone, Debug, Deserialize, Serialize, ToResponse)]
+pub struct JobStatus {
+    /// 任务名
+    #[serde(rename = "_id")]
+    pub job: String,
+    /// cron 表达式
+    pub schedule: String,
+    /// 下一次计划运行时间（含随机延迟）
+    pub next_run_at: Option<DateTime>,
+    /// 最近一次开始运行的时间
+    pub last_started_at: Option<DateTime>,
+    /// 最近一次结束运行的时间
+    pub last_finished_at: Option<DateTime>,
+    /// 最近一次运行结果
+    pub last_status: Option<JobRunStatus>,
+    /// 最近一次运行失败的原因
+    pub last_error: Option<String>,
+    /// 最近一次运行所在的实例
+    pub last_owner: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, ToResponse)]
 pub struct Comment {
     /// 评论ID
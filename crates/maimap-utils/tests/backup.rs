@@ -0,0 +1,494 @@
+#[cfg(test)]
+mod tests {
+    use aes_gcm::aead::{Aead, KeyInit, Payload};
+    use aes_gcm::{Aes256Gcm, Key, Nonce};
+    use chrono::Utc;
+    use maimap_utils::backup::BackupFormat;
+    use maimap_utils::backup::crypto::{
+        BackupKeyring, ENCRYPTION_ALGORITHM, EncryptionInfo, decrypt_file, encrypt_file,
+        encrypted_key_id,
+    };
+    use maimap_utils::backup::manifest::{
+        BackupManifest, manifest_key, sha256_file, verify_archive, verify_local_backup,
+        write_manifest,
+    };
+    use maimap_utils::backup::native::{
+        ArchiveReader, ArchiveRecord, ArchiveWriter, is_native_archive,
+    };
+    use maimap_utils::backup::retention::RetentionPolicy;
+    use maimap_utils::backup::store::{BackupStore, LocalStore};
+    use maimap_utils::backup::{
+        RestoredCollection, list_backups, parse_restore_log, resolve_backup,
+    };
+    use maimap_utils::db::doc;
+    use mongodb::bson::RawDocumentBuf;
+
+    #[test]
+    fn test_parse_restore_log() {
+        let log = "\
+2025-04-14T20:06:00.123+0800\tpreparing collections to restore from
+2025-04-14T20:06:00.456+0800\tfinished restoring staging.arcades (2093 documents, 0 failures)
+2025-04-14T20:06:00.789+0800\tfinished restoring staging.comments (12 documents, 1 failures)
+2025-04-14T20:06:01.000+0800\t2105 document(s) restored successfully. 1 document(s) failed to restore.";
+
+        assert_eq!(
+            parse_restore_log(log),
+            vec![
+                RestoredCollection {
+                    name: "arcades".to_string(),
+                    documents: 2093,
+                    failures: 0,
+                },
+                RestoredCollection {
+                    name: "comments".to_string(),
+                    documents: 12,
+                    failures: 1,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_backup() {
+        let dir = std::env::temp_dir().join(format!("maimap-resolve-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = LocalStore::new(&dir);
+
+        assert!(resolve_backup(&store, None).await.is_err());
+
+        for key in [
+            "maimap_1744632360.gz",
+            "maimap_1744718760.gz",
+            "maimap_1744718760.gz.manifest.json",
+            "maimap_1744805160.bson.gz.enc",
+            "notes.txt",
+        ] {
+            let file = dir.join(format!("source_{}", key));
+            std::fs::write(&file, key).unwrap();
+            store.put(key, &file).await.unwrap();
+            std::fs::remove_file(&file).unwrap();
+        }
+
+        let keys: Vec<String> = list_backups(&store)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|backup| backup.key)
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                "maimap_1744632360.gz",
+                "maimap_1744718760.gz",
+                "maimap_1744805160.bson.gz.enc"
+            ]
+        );
+
+        assert_eq!(
+            resolve_backup(&store, None).await.unwrap(),
+            "maimap_1744805160.bson.gz.enc"
+        );
+        assert_eq!(
+            resolve_backup(&store, Some("maimap_1744632360.gz"))
+                .await
+                .unwrap(),
+            "maimap_1744632360.gz"
+        );
+        assert!(resolve_backup(&store, Some("notes.txt")).await.is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_retention_policy() {
+        // 2025-01-01 至 2025-04-10 每天中午一份备份
+        let keys: Vec<String> = (0..100)
+            .map(|day| format!("maimap_{}.gz", 1735732800 + day * 86400))
+            .chain(["maimap_manual.gz".to_string()])
+            .collect();
+        let policy = RetentionPolicy {
+            daily: 7,
+            weekly: 4,
+            monthly: 6,
+        };
+
+        let expired = policy.expired(&keys, &Utc);
+        let mut kept: Vec<&str> = keys
+            .iter()
+            .filter(|key| !expired.contains(key))
+            .map(String::as_str)
+            .collect();
+        kept.sort();
+        assert_eq!(
+            kept,
+            [
+                // 每月最新：1 月 31 日、2 月 28 日、3 月 31 日（4 月 10 日同时是每日备份）
+                "maimap_1738324800.gz",
+                "maimap_1740744000.gz",
+                // 每周最新：3 月 23 日、3 月 30 日、4 月 6 日
+                "maimap_1742731200.gz",
+                "maimap_1743336000.gz",
+                "maimap_1743422400.gz",
+                // 最近 7 天：4 月 4 日至 4 月 10 日
+                "maimap_1743768000.gz",
+                "maimap_1743854400.gz",
+                "maimap_1743940800.gz",
+                "maimap_1744027200.gz",
+                "maimap_1744113600.gz",
+                "maimap_1744200000.gz",
+                "maimap_1744286400.gz",
+                // 无法读取时间戳的文件不删除
+                "maimap_manual.gz",
+            ]
+        );
+
+        let disabled = RetentionPolicy {
+            daily: 0,
+            weekly: 0,
+            monthly: 0,
+        };
+        assert!(disabled.expired(&keys, &Utc).is_empty());
+
+        let latest_only = RetentionPolicy {
+            daily: 1,
+            weekly: 0,
+            monthly: 0,
+        };
+        assert_eq!(latest_only.expired(&keys, &Utc).len(), 99);
+
+        // 原生格式的备份同样按时间戳参与清理
+        let mixed = [
+            "maimap_1.gz".to_string(),
+            "maimap_2.bson.gz".to_string(),
+            "maimap_3.bson.gz.enc".to_string(),
+        ];
+        assert_eq!(
+            latest_only.expired(&mixed, &Utc),
+            ["maimap_2.bson.gz", "maimap_1.gz"]
+        );
+    }
+
+    #[test]
+    fn test_native_archive_round_trip() {
+        let arcade =
+            RawDocumentBuf::from_document(&doc! { "arcade_id": 1, "arcade_name": "机厅" }).unwrap();
+        let tag = RawDocumentBuf::from_document(&doc! { "arcade_id": 1, "name": "标签" }).unwrap();
+
+        let mut buffer = Vec::new();
+        let mut writer = ArchiveWriter::new(&mut buffer).unwrap();
+        writer.begin_collection("arcades").unwrap();
+        writer.write_document(&arcade).unwrap();
+        writer.write_document(&arcade).unwrap();
+        writer.begin_collection("comments").unwrap();
+        writer.begin_collection("tags").unwrap();
+        writer.write_document(&tag).unwrap();
+        let manifest = writer.finish("maimap").unwrap();
+
+        let counts: Vec<(&str, u64)> = manifest
+            .collections
+            .iter()
+            .map(|collection| (collection.name.as_str(), collection.count))
+            .collect();
+        assert_eq!(counts, [("arcades", 2), ("comments", 0), ("tags", 1)]);
+
+        let mut reader = ArchiveReader::new(buffer.as_slice()).unwrap();
+        let mut records = Vec::new();
+        while let Some(record) = reader.next_record().unwrap() {
+            records.push(match record {
+                ArchiveRecord::Collection(name) => format!("collection {}", name),
+                ArchiveRecord::Document(document) => {
+                    format!("document {}", document.to_document().unwrap())
+                }
+                ArchiveRecord::Manifest(found) => {
+                    assert_eq!(found, manifest);
+                    "manifest".to_string()
+                }
+            });
+        }
+        assert_eq!(
+            records,
+            [
+                "collection arcades".to_string(),
+                format!("document {}", arcade.to_document().unwrap()),
+                format!("document {}", arcade.to_document().unwrap()),
+                "collection comments".to_string(),
+                "collection tags".to_string(),
+                format!("document {}", tag.to_document().unwrap()),
+                "manifest".to_string(),
+            ]
+        );
+
+        // 截断的备份在读取时报错
+        let mut truncated = ArchiveReader::new(&buffer[..buffer.len() - 12]).unwrap();
+        let mut result = Ok(None);
+        for _ in 0..7 {
+            result = truncated.next_record();
+            if result.is_err() {
+                break;
+            }
+        }
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_is_native_archive() {
+        let dir = std::env::temp_dir().join(format!("maimap-native-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let native = dir.join("maimap_1.bson.gz");
+        let file = std::fs::File::create(&native).unwrap();
+        ArchiveWriter::new(file).unwrap().finish("maimap").unwrap();
+        assert!(is_native_archive(&native).unwrap());
+
+        let other = dir.join("maimap_1.gz");
+        std::fs::write(&other, b"not gzip").unwrap();
+        assert!(!is_native_archive(&other).unwrap());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_verify_local_backup() {
+        let dir = std::env::temp_dir().join(format!("maimap-verify-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let archive = dir.join("maimap_1744632360.bson.gz");
+        let mut writer = ArchiveWriter::new(std::fs::File::create(&archive).unwrap()).unwrap();
+        writer.begin_collection("arcades").unwrap();
+        for id in 0..3 {
+            let document = RawDocumentBuf::from_document(&doc! { "arcade_id": id }).unwrap();
+            writer.write_document(&document).unwrap();
+        }
+        let collections = writer.finish("maimap").unwrap().collections;
+
+        let (sha256, size) = sha256_file(&archive).unwrap();
+        let manifest = BackupManifest {
+            archive: "maimap_1744632360.bson.gz".to_string(),
+            format: BackupFormat::Native,
+            database: "maimap".to_string(),
+            created_at: "2025-04-14T20:06:00+08:00".to_string(),
+            size,
+            sha256,
+            collections,
+            encryption: None,
+        };
+        let manifest_path = dir.join(manifest_key("maimap_1744632360.bson.gz"));
+        assert!(manifest_path.ends_with("maimap_1744632360.bson.gz.manifest.json"));
+        write_manifest(&manifest_path, &manifest).unwrap();
+
+        assert_eq!(verify_local_backup(&archive).unwrap(), manifest);
+
+        // 截断的备份无法通过校验
+        let content = std::fs::read(&archive).unwrap();
+        std::fs::write(&archive, &content[..content.len() / 2]).unwrap();
+        let error = format!("{:#}", verify_local_backup(&archive).unwrap_err());
+        assert!(error.contains("文件大小"), "{}", error);
+        assert!(error.contains("SHA-256"), "{}", error);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_sha256_file() {
+        let path = std::env::temp_dir().join(format!("maimap-sha256-{}", std::process::id()));
+        std::fs::write(&path, b"abc").unwrap();
+        assert_eq!(
+            sha256_file(&path).unwrap(),
+            (
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad".to_string(),
+                3
+            )
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    const OLD_KEY: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+    const NEW_KEY: &str = "00000000000000000000000000000000000000000000000000000000000000ff";
+
+    #[test]
+    fn test_parse_keyring() {
+        let keyring =
+            BackupKeyring::parse(&format!("2024:{}, 2025:{}", OLD_KEY, NEW_KEY), "2025").unwrap();
+        assert_eq!(keyring.current().unwrap().id(), "2025");
+
+        // 未指定当前密钥时不加密新备份，但仍可解密旧备份
+        let keyring = BackupKeyring::parse(&format!("2024:{}", OLD_KEY), "").unwrap();
+        assert!(keyring.current().is_none());
+        assert!(BackupKeyring::parse("", "").unwrap().current().is_none());
+
+        assert!(BackupKeyring::parse(OLD_KEY, "").is_err());
+        assert!(BackupKeyring::parse("2024:abcd", "").is_err());
+        assert!(BackupKeyring::parse(&format!("2024:{}", OLD_KEY), "2025").is_err());
+    }
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let dir = std::env::temp_dir().join(format!("maimap-crypto-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let plain = dir.join("maimap_1744632360.gz");
+        let encrypted = dir.join("maimap_1744632360.gz.enc");
+        let decrypted = dir.join("decrypted_maimap_1744632360.gz");
+        std::fs::write(&plain, b"mongodump archive").unwrap();
+
+        let old = BackupKeyring::parse(&format!("2024:{}", OLD_KEY), "2024").unwrap();
+        encrypt_file(old.current().unwrap(), &plain, &encrypted).unwrap();
+        assert_eq!(
+            encrypted_key_id(&encrypted).unwrap().as_deref(),
+            Some("2024")
+        );
+        assert_eq!(encrypted_key_id(&plain).unwrap(), None);
+        assert_ne!(std::fs::read(&encrypted).unwrap(), b"mongodump archive");
+
+        // 轮换到新密钥后，旧备份仍按头部记录的 ID 选择旧密钥解密
+        let rotated =
+            BackupKeyring::parse(&format!("2024:{},2025:{}", OLD_KEY, NEW_KEY), "2025").unwrap();
+        assert_eq!(
+            decrypt_file(&rotated, &encrypted, &decrypted).unwrap(),
+            "2024"
+        );
+        assert_eq!(std::fs::read(&decrypted).unwrap(), b"mongodump archive");
+
+        // 缺少旧密钥时无法解密
+        let new_only = BackupKeyring::parse(&format!("2025:{}", NEW_KEY), "2025").unwrap();
+        assert!(decrypt_file(&new_only, &encrypted, &decrypted).is_err());
+
+        // 同一 ID 下密钥不正确或文件被篡改时解密失败
+        let wrong = BackupKeyring::parse(&format!("2024:{}", NEW_KEY), "").unwrap();
+        assert!(decrypt_file(&wrong, &encrypted, &decrypted).is_err());
+        let mut content = std::fs::read(&encrypted).unwrap();
+        *content.last_mut().unwrap() ^= 1;
+        std::fs::write(&encrypted, content).unwrap();
+        assert!(decrypt_file(&old, &encrypted, &decrypted).is_err());
+
+        assert!(decrypt_file(&old, &plain, &decrypted).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_encrypt_multiple_chunks() {
+        let dir = std::env::temp_dir().join(format!("maimap-crypto-chunks-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let plain = dir.join("maimap_1744632360.gz");
+        let encrypted = dir.join("maimap_1744632360.gz.enc");
+        let decrypted = dir.join("decrypted_maimap_1744632360.gz");
+        let keyring = BackupKeyring::parse(&format!("2024:{}", OLD_KEY), "2024").unwrap();
+
+        // 空文件、恰好整块与跨越多块的文件都能还原
+        for size in [0, 64 * 1024, 200_000] {
+            let content: Vec<u8> = (0..size).map(|i| (i % 251) as u8).collect();
+            std::fs::write(&plain, &content).unwrap();
+            encrypt_file(keyring.current().unwrap(), &plain, &encrypted).unwrap();
+            decrypt_file(&keyring, &encrypted, &decrypted).unwrap();
+            assert_eq!(std::fs::read(&decrypted).unwrap(), content, "{} 字节", size);
+        }
+
+        // 在块边界处截断后解密失败，且不留下解密了一半的文件
+        let content = std::fs::read(&encrypted).unwrap();
+        let header_length = 8 + 1 + "2024".len() + 7;
+        std::fs::write(&encrypted, &content[..header_length + 64 * 1024 + 16]).unwrap();
+        std::fs::remove_file(&decrypted).unwrap();
+        assert!(decrypt_file(&keyring, &encrypted, &decrypted).is_err());
+        assert!(!decrypted.exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_decrypt_legacy_format() {
+        let dir = std::env::temp_dir().join(format!("maimap-crypto-legacy-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // 旧格式：标识、密钥 ID 与 12 字节随机数之后是整个文件的密文
+        let nonce = [7u8; 12];
+        let mut header = b"MAIMAPE1".to_vec();
+        header.push(4);
+        header.extend_from_slice(b"2024");
+        header.extend_from_slice(&nonce);
+        let key = hex::decode(OLD_KEY).unwrap();
+        let ciphertext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: b"mongodump archive",
+                    aad: &header,
+                },
+            )
+            .unwrap();
+        let encrypted = dir.join("maimap_1744632360.gz.enc");
+        let decrypted = dir.join("decrypted_maimap_1744632360.gz");
+        std::fs::write(&encrypted, [header, ciphertext].concat()).unwrap();
+
+        let keyring = BackupKeyring::parse(&format!("2024:{}", OLD_KEY), "").unwrap();
+        assert_eq!(
+            encrypted_key_id(&encrypted).unwrap().as_deref(),
+            Some("2024")
+        );
+        assert_eq!(
+            decrypt_file(&keyring, &encrypted, &decrypted).unwrap(),
+            "2024"
+        );
+        assert_eq!(std::fs::read(&decrypted).unwrap(), b"mongodump archive");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_verify_encrypted_archive() {
+        let dir = std::env::temp_dir().join(format!("maimap-verify-enc-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let plain = dir.join("maimap_1744632360.bson.gz");
+        let mut writer = ArchiveWriter::new(std::fs::File::create(&plain).unwrap()).unwrap();
+        writer.begin_collection("arcades").unwrap();
+        let document = RawDocumentBuf::from_document(&doc! { "arcade_id": 1 }).unwrap();
+        writer.write_document(&document).unwrap();
+        let collections = writer.finish("maimap").unwrap().collections;
+
+        let keyring = BackupKeyring::parse(&format!("2024:{}", OLD_KEY), "2024").unwrap();
+        let archive = dir.join("maimap_1744632360.bson.gz.enc");
+        encrypt_file(keyring.current().unwrap(), &plain, &archive).unwrap();
+
+        let (sha256, size) = sha256_file(&archive).unwrap();
+        let mut manifest = BackupManifest {
+            archive: "maimap_1744632360.bson.gz.enc".to_string(),
+            format: BackupFormat::Native,
+            database: "maimap".to_string(),
+            created_at: "2025-04-14T20:06:00+08:00".to_string(),
+            size,
+            sha256,
+            collections,
+            encryption: Some(EncryptionInfo {
+                algorithm: ENCRYPTION_ALGORITHM.to_string(),
+                key_id: "2024".to_string(),
+            }),
+        };
+        assert!(
+            verify_archive(&archive, &manifest, &keyring)
+                .unwrap()
+                .is_empty()
+        );
+
+        // 解密失败时无法统计文档数
+        let empty = BackupKeyring::parse("", "").unwrap();
+        let problems = verify_archive(&archive, &manifest, &empty).unwrap();
+        assert_eq!(problems.len(), 1, "{:?}", problems);
+
+        // 清单记录的密钥与文件头部不一致
+        manifest.encryption = None;
+        let problems = verify_archive(&archive, &manifest, &keyring).unwrap();
+        assert_eq!(problems.len(), 1, "{:?}", problems);
+        assert!(problems[0].contains("加密密钥"), "{:?}", problems);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
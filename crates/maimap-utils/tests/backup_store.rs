@@ -0,0 +1,277 @@
+#[cfg(test)]
+mod tests {
+    use maimap_utils::backup::store::{
+        BackupObject, BackupStore, BackupStoreKind, LocalStore, S3Store, parse_list_objects,
+    };
+    use maimap_utils::errors::AppError;
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    /// 与 S3Store 分块上传时每块的大小一致
+    const PART_SIZE: usize = 8 * 1024 * 1024;
+
+    /// 模拟 S3 收到的请求：方法、路径与查询参数、请求体
+    type Requests = Arc<Mutex<Vec<(String, String, Vec<u8>)>>>;
+
+    /// 启动模拟 S3 分块上传的本地服务，返回其地址与收到的请求。
+    /// `fail_part` 指定的分块返回 500。
+    async fn serve_s3(fail_part: Option<usize>) -> (String, Requests) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Requests::default();
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut stream = BufReader::new(stream);
+                let mut request_line = String::new();
+                stream.read_line(&mut request_line).await.unwrap();
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    stream.read_line(&mut line).await.unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':')
+                        && name.eq_ignore_ascii_case("content-length")
+                    {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0u8; length];
+                stream.read_exact(&mut body).await.unwrap();
+
+                let mut parts = request_line.split_whitespace();
+                let method = parts.next().unwrap().to_string();
+                let target = parts.next().unwrap().to_string();
+                let (status, headers, content) = if target.ends_with("?uploads=") {
+                    let xml = "<InitiateMultipartUploadResult><UploadId>upload-1</UploadId></InitiateMultipartUploadResult>";
+                    ("200 OK", String::new(), xml.to_string())
+                } else if let Some((_, number)) = target.split_once("partNumber=") {
+                    let number: usize = number.split('&').next().unwrap().parse().unwrap();
+                    if fail_part == Some(number) {
+                        ("500 Internal Server Error", String::new(), String::new())
+                    } else {
+                        (
+                            "200 OK",
+                            format!("ETag: \"etag-{}\"\r\n", number),
+                            String::new(),
+                        )
+                    }
+                } else if method == "DELETE" {
+                    ("204 No Content", String::new(), String::new())
+                } else {
+                    (
+                        "200 OK",
+                        String::new(),
+                        "<CompleteMultipartUploadResult/>".to_string(),
+                    )
+                };
+                recorded.lock().unwrap().push((method, target, body));
+
+                let response = format!(
+                    "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    headers,
+                    content.len(),
+                    content
+                );
+                stream
+                    .get_mut()
+                    .write_all(response.as_bytes())
+                    .await
+                    .unwrap();
+            }
+        });
+        (format!("http://{}", addr), requests)
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("maimap-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_local_store_round_trip() {
+        let dir = temp_dir("local-store");
+        let store = LocalStore::new(dir.join("store"));
+        let source = dir.join("maimap_1.gz");
+        std::fs::write(&source, b"backup").unwrap();
+
+        assert!(store.list().await.unwrap().is_empty());
+        store.put("maimap_1.gz", &source).await.unwrap();
+        assert_eq!(
+            store.list().await.unwrap(),
+            vec![BackupObject {
+                key: "maimap_1.gz".to_string(),
+                size: 6,
+            }]
+        );
+
+        let target = dir.join("downloaded.gz");
+        store.get("maimap_1.gz", &target).await.unwrap();
+        assert_eq!(std::fs::read(&target).unwrap(), b"backup");
+
+        store.delete("maimap_1.gz").await.unwrap();
+        store.delete("maimap_1.gz").await.unwrap();
+        assert!(store.list().await.unwrap().is_empty());
+
+        assert!(store.put("../escape.gz", &source).await.is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_list_objects() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <Name>maimap</Name>
+  <IsTruncated>true</IsTruncated>
+  <Contents><Key>maimap_1.gz</Key><Size>10</Size></Contents>
+  <Contents><Key>maimap_2.gz</Key><Size>20</Size></Contents>
+  <NextContinuationToken>token&amp;1</NextContinuationToken>
+</ListBucketResult>"#;
+
+        let (objects, token) = parse_list_objects(xml).unwrap();
+        assert_eq!(
+            objects,
+            vec![
+                BackupObject {
+                    key: "maimap_1.gz".to_string(),
+                    size: 10,
+                },
+                BackupObject {
+                    key: "maimap_2.gz".to_string(),
+                    size: 20,
+                },
+            ]
+        );
+        assert_eq!(token.as_deref(), Some("token&1"));
+
+        let last_page = xml.replace("<IsTruncated>true", "<IsTruncated>false");
+        assert_eq!(parse_list_objects(&last_page).unwrap().1, None);
+    }
+
+    #[test]
+    fn test_backup_store_kind() {
+        assert_eq!(
+            "local".parse::<BackupStoreKind>().unwrap(),
+            BackupStoreKind::Local
+        );
+        assert_eq!(
+            "S3".parse::<BackupStoreKind>().unwrap(),
+            BackupStoreKind::S3
+        );
+        assert_eq!(
+            "oss".parse::<BackupStoreKind>().unwrap(),
+            BackupStoreKind::Oss
+        );
+        assert!("ftp".parse::<BackupStoreKind>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_s3_endpoint_with_path_rejected() {
+        for endpoint in ["http://127.0.0.1:9000/s3", "http://127.0.0.1:9000/?a=b"] {
+            let store = S3Store::new(endpoint, "us-east-1", "maimap", "id", "secret");
+            let err = store.list().await.unwrap_err();
+            assert!(
+                matches!(
+                    err.downcast_ref::<AppError>(),
+                    Some(AppError::Configuration(_))
+                ),
+                "{}: {:?}",
+                endpoint,
+                err
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_s3_multipart_upload() {
+        let dir = temp_dir("s3-multipart");
+        let file = dir.join("maimap_1744632360.gz");
+        let content: Vec<u8> = (0..PART_SIZE + 10).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&file, &content).unwrap();
+
+        let (endpoint, requests) = serve_s3(None).await;
+        let store = S3Store::new(endpoint, "us-east-1", "maimap", "id", "secret");
+        store.put("maimap_1744632360.gz", &file).await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        let summary: Vec<_> = requests
+            .iter()
+            .map(|(method, target, body)| (method.as_str(), target.as_str(), body.len()))
+            .collect();
+        assert_eq!(
+            summary[..3],
+            [
+                ("POST", "/maimap/maimap_1744632360.gz?uploads=", 0),
+                (
+                    "PUT",
+                    "/maimap/maimap_1744632360.gz?partNumber=1&uploadId=upload-1",
+                    PART_SIZE
+                ),
+                (
+                    "PUT",
+                    "/maimap/maimap_1744632360.gz?partNumber=2&uploadId=upload-1",
+                    10
+                ),
+            ]
+        );
+        let (method, target, body) = &requests[3];
+        assert_eq!(method, "POST");
+        assert_eq!(target, "/maimap/maimap_1744632360.gz?uploadId=upload-1");
+        assert_eq!(
+            String::from_utf8_lossy(body),
+            "<CompleteMultipartUpload>\
+             <Part><PartNumber>1</PartNumber><ETag>\"etag-1\"</ETag></Part>\
+             <Part><PartNumber>2</PartNumber><ETag>\"etag-2\"</ETag></Part>\
+             </CompleteMultipartUpload>"
+        );
+        assert_eq!(requests.len(), 4);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_s3_multipart_upload_aborts_on_failure() {
+        let dir = temp_dir("s3-multipart-abort");
+        let file = dir.join("maimap_1744632360.gz");
+        std::fs::write(&file, vec![0u8; PART_SIZE + 10]).unwrap();
+
+        let (endpoint, requests) = serve_s3(Some(2)).await;
+        let store = S3Store::new(endpoint, "us-east-1", "maimap", "id", "secret");
+        assert!(store.put("maimap_1744632360.gz", &file).await.is_err());
+
+        // 上传分块失败后取消整个上传
+        let requests = requests.lock().unwrap();
+        let (method, target, _) = requests.last().unwrap();
+        assert_eq!(method, "DELETE");
+        assert_eq!(target, "/maimap/maimap_1744632360.gz?uploadId=upload-1");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_s3_small_file_single_put() {
+        let dir = temp_dir("s3-single-put");
+        let file = dir.join("maimap_1744632360.gz");
+        std::fs::write(&file, b"mongodump archive").unwrap();
+
+        let (endpoint, requests) = serve_s3(None).await;
+        let store = S3Store::new(endpoint, "us-east-1", "maimap", "id", "secret");
+        store.put("maimap_1744632360.gz", &file).await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].0, "PUT");
+        assert_eq!(requests[0].1, "/maimap/maimap_1744632360.gz");
+        assert_eq!(requests[0].2, b"mongodump archive");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,147 @@
+#[cfg(test)]
+mod tests {
+    use maimap_utils::db::{
+        Arcade, ArcadeEvent, ArcadeWrites, Collection, DateTime, Decimal128, WriteCapabilities,
+        apply_arcade_writes, doc, ensure_test_mongodb_connected, get_mongodb_client,
+    };
+    use maimap_utils::env::DB_NAME;
+    use maimap_utils::types::ArcadeEventKind;
+    use std::str::FromStr;
+
+    fn capabilities(transactions: bool, bulk_write: bool) -> WriteCapabilities {
+        WriteCapabilities {
+            transactions,
+            bulk_write,
+        }
+    }
+
+    #[test]
+    fn test_write_capabilities_standalone() {
+        let hello = doc! { "isWritablePrimary": true, "maxWireVersion": 21 };
+        assert_eq!(
+            WriteCapabilities::from_hello(&hello),
+            capabilities(false, false)
+        );
+
+        // MongoDB 8.0 单机部署只能使用 bulkWrite，不能使用事务
+        let hello = doc! { "isWritablePrimary": true, "maxWireVersion": 25 };
+        assert_eq!(
+            WriteCapabilities::from_hello(&hello),
+            capabilities(false, true)
+        );
+    }
+
+    #[test]
+    fn test_write_capabilities_replica_set() {
+        let hello = doc! {
+            "isWritablePrimary": true,
+            "setName": "rs0",
+            "hosts": ["mongo-0:27017", "mongo-1:27017"],
+            "maxWireVersion": 21,
+        };
+        assert_eq!(
+            WriteCapabilities::from_hello(&hello),
+            capabilities(true, false)
+        );
+
+        let hello = doc! { "isWritablePrimary": true, "setName": "rs0", "maxWireVersion": 25_i64 };
+        assert_eq!(
+            WriteCapabilities::from_hello(&hello),
+            capabilities(true, true)
+        );
+    }
+
+    #[test]
+    fn test_write_capabilities_sharded_cluster() {
+        let hello = doc! { "isWritablePrimary": true, "msg": "isdbgrid", "maxWireVersion": 25 };
+        assert_eq!(
+            WriteCapabilities::from_hello(&hello),
+            capabilities(true, true)
+        );
+    }
+
+    #[test]
+    fn test_write_capabilities_unknown_wire_version() {
+        let hello = doc! { "isWritablePrimary": true, "setName": "rs0" };
+        assert_eq!(
+            WriteCapabilities::from_hello(&hello),
+            capabilities(true, false)
+        );
+    }
+
+    fn arcade(id: i32, name: &str) -> Arcade {
+        Arcade {
+            arcade_address: "测试地址".to_string(),
+            arcade_cost: None,
+            arcade_count: None,
+            arcade_dead: false,
+            arcade_id: id,
+            arcade_lat: Decimal128::from_str("39.9").unwrap(),
+            arcade_lng: Decimal128::from_str("116.4").unwrap(),
+            arcade_pos: None,
+            arcade_name: name.to_string(),
+            arcade_store_id: None,
+            arcade_province: None,
+            arcade_machine: None,
+            arcade_missing_count: 0,
+            created_at: DateTime::now(),
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URI"]
+    async fn test_apply_arcade_writes() {
+        ensure_test_mongodb_connected().await;
+        let database = get_mongodb_client().database(DB_NAME);
+        let arcades: Collection<Arcade> = database.collection("arcades");
+        let events: Collection<ArcadeEvent> = database.collection("arcade_events");
+
+        // 使用负数ID，避免与真实机厅冲突
+        let ids: Vec<i32> = (1..=3)
+            .map(|i| -(std::process::id() as i32) * 10 - i)
+            .collect();
+        let filter = doc! { "arcade_id": { "$in": &ids } };
+
+        apply_arcade_writes(&ArcadeWrites {
+            inserted: ids.iter().map(|&id| arcade(id, "旧名称")).collect(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        let mut closed = arcade(ids[0], "新名称");
+        closed.arcade_dead = true;
+        let event = ArcadeEvent::new(&closed, ArcadeEventKind::Closed, DateTime::now());
+        let result = apply_arcade_writes(&ArcadeWrites {
+            replaced: vec![closed, arcade(ids[1], "新名称")],
+            events: vec![event],
+            ..Default::default()
+        })
+        .await;
+
+        // 替换不存在的机厅会失败
+        let missing = apply_arcade_writes(&ArcadeWrites {
+            replaced: vec![arcade(-1, "不存在")],
+            ..Default::default()
+        })
+        .await;
+
+        let renamed = arcades
+            .count_documents(doc! { "arcade_id": { "$in": &ids }, "arcade_name": "新名称" })
+            .await
+            .unwrap();
+        let dead = arcades
+            .count_documents(doc! { "arcade_id": { "$in": &ids }, "arcade_dead": true })
+            .await
+            .unwrap();
+        let event_count = events.count_documents(filter.clone()).await.unwrap();
+        arcades.delete_many(filter.clone()).await.unwrap();
+        events.delete_many(filter).await.unwrap();
+
+        result.unwrap();
+        assert!(missing.is_err());
+        assert_eq!(renamed, 2);
+        assert_eq!(dead, 1);
+        assert_eq!(event_count, 1);
+    }
+}
@@ -0,0 +1,189 @@
+#[cfg(test)]
+mod tests {
+    use maimap_utils::db::{Document, doc};
+    use maimap_utils::indexes::{IndexDrift, IndexSpec, compare_indexes, index_specs};
+    use mongodb::IndexModel;
+    use mongodb::options::IndexOptions;
+
+    fn index(name: &str, keys: Document, unique: bool) -> IndexModel {
+        IndexModel::builder()
+            .keys(keys)
+            .options(
+                IndexOptions::builder()
+                    .name(name.to_string())
+                    .unique(unique.then_some(true))
+                    .build(),
+            )
+            .build()
+    }
+
+    fn specs() -> Vec<IndexSpec> {
+        vec![
+            IndexSpec {
+                collection: "arcades",
+                name: "arcade_id_unique",
+                keys: doc! { "arcade_id": 1 },
+                unique: true,
+            },
+            IndexSpec {
+                collection: "arcades",
+                name: "arcade_pos_2dsphere",
+                keys: doc! { "arcade_pos": "2dsphere" },
+                unique: false,
+            },
+            IndexSpec {
+                collection: "comments",
+                name: "arcade_id_created_at",
+                keys: doc! { "arcade_id": 1, "created_at": -1 },
+                unique: false,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_compare_indexes_in_sync() {
+        let existing = vec![
+            index("_id_", doc! { "_id": 1 }, false),
+            // 数据库返回的数值类型可能与声明不同
+            index("arcade_id_unique", doc! { "arcade_id": 1_i64 }, true),
+            index(
+                "arcade_pos_2dsphere",
+                doc! { "arcade_pos": "2dsphere" },
+                false,
+            ),
+        ];
+        assert!(compare_indexes("arcades", &specs(), &existing).is_empty());
+    }
+
+    #[test]
+    fn test_compare_indexes_missing() {
+        let existing = vec![index("_id_", doc! { "_id": 1 }, false)];
+        assert_eq!(
+            compare_indexes("arcades", &specs(), &existing),
+            vec![
+                IndexDrift::Missing {
+                    collection: "arcades".to_string(),
+                    name: "arcade_id_unique".to_string(),
+                },
+                IndexDrift::Missing {
+                    collection: "arcades".to_string(),
+                    name: "arcade_pos_2dsphere".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compare_indexes_mismatched_by_name() {
+        let existing = vec![
+            index("arcade_id_unique", doc! { "arcade_id": 1 }, false),
+            index(
+                "arcade_pos_2dsphere",
+                doc! { "arcade_pos": "2dsphere" },
+                false,
+            ),
+        ];
+        assert_eq!(
+            compare_indexes("arcades", &specs(), &existing),
+            vec![IndexDrift::Mismatched {
+                collection: "arcades".to_string(),
+                name: "arcade_id_unique".to_string(),
+                expected: "{arcade_id:1} unique".to_string(),
+                actual: "{arcade_id:1}".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_compare_indexes_matched_by_keys() {
+        // 名称不同但字段与唯一性相同，视为满足声明，不报告为未声明
+        let existing = vec![index(
+            "arcade_id_1_created_at_-1",
+            doc! { "arcade_id": 1, "created_at": -1 },
+            false,
+        )];
+        assert!(compare_indexes("comments", &specs(), &existing).is_empty());
+
+        // 字段顺序不同时不视为同一个索引
+        let existing = vec![index(
+            "created_at_-1_arcade_id_1",
+            doc! { "created_at": -1, "arcade_id": 1 },
+            false,
+        )];
+        assert_eq!(
+            compare_indexes("comments", &specs(), &existing),
+            vec![
+                IndexDrift::Missing {
+                    collection: "comments".to_string(),
+                    name: "arcade_id_created_at".to_string(),
+                },
+                IndexDrift::Undeclared {
+                    collection: "comments".to_string(),
+                    name: "created_at_-1_arcade_id_1".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compare_indexes_undeclared() {
+        let existing = vec![
+            index("_id_", doc! { "_id": 1 }, false),
+            index("arcade_id_unique", doc! { "arcade_id": 1 }, true),
+            index(
+                "arcade_pos_2dsphere",
+                doc! { "arcade_pos": "2dsphere" },
+                false,
+            ),
+            index("arcade_name_1", doc! { "arcade_name": 1 }, false),
+        ];
+        assert_eq!(
+            compare_indexes("arcades", &specs(), &existing),
+            vec![IndexDrift::Undeclared {
+                collection: "arcades".to_string(),
+                name: "arcade_name_1".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_index_specs_unique_names() {
+        let specs = index_specs();
+        for (i, spec) in specs.iter().enumerate() {
+            assert!(
+                !specs[..i]
+                    .iter()
+                    .any(|other| other.collection == spec.collection && other.name == spec.name),
+                "{}.{} 重复声明",
+                spec.collection,
+                spec.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_index_drift_breaks_uniqueness() {
+        let specs = specs();
+        let missing = |name: &str| IndexDrift::Missing {
+            collection: "arcades".to_string(),
+            name: name.to_string(),
+        };
+        assert!(missing("arcade_id_unique").breaks_uniqueness(&specs));
+        assert!(!missing("arcade_pos_2dsphere").breaks_uniqueness(&specs));
+
+        // 同名索引存在但不是唯一索引
+        let mismatched = IndexDrift::Mismatched {
+            collection: "arcades".to_string(),
+            name: "arcade_id_unique".to_string(),
+            expected: "{arcade_id:1} unique".to_string(),
+            actual: "{arcade_id:1}".to_string(),
+        };
+        assert!(mismatched.breaks_uniqueness(&specs));
+
+        let undeclared = IndexDrift::Undeclared {
+            collection: "arcades".to_string(),
+            name: "arcade_id_unique".to_string(),
+        };
+        assert!(!undeclared.breaks_uniqueness(&specs));
+    }
+}
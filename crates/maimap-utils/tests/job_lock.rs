@@ -0,0 +1,138 @@
+#[cfg(test)]
+mod tests {
+    use maimap_utils::db::{
+        DateTime, ensure_test_mongodb_connected, release_job_lock, renew_job_lock,
+        run_with_heartbeat, try_acquire_job_lock,
+    };
+    use std::cell::Cell;
+    use std::time::Duration;
+
+    /// 需要 `TEST_DATABASE_URI` 指向可用的 MongoDB。
+    /// 返回本次测试专用的任务名，避免与其他测试或之前的运行冲突。
+    async fn job(name: &str) -> String {
+        ensure_test_mongodb_connected().await;
+        format!(
+            "test_{}_{}_{}",
+            name,
+            std::process::id(),
+            DateTime::now().timestamp_millis()
+        )
+    }
+
+    fn slot(millis: i64) -> DateTime {
+        DateTime::from_millis(1744632360000 + millis)
+    }
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URI"]
+    async fn test_job_lock_acquire_and_skip_slot() {
+        let job = job("acquire").await;
+
+        assert!(
+            try_acquire_job_lock(&job, slot(0), "a:1", TTL)
+                .await
+                .unwrap()
+        );
+        // 锁被占用时其他实例拿不到锁
+        assert!(
+            !try_acquire_job_lock(&job, slot(1000), "b:1", TTL)
+                .await
+                .unwrap()
+        );
+
+        release_job_lock(&job, "a:1").await.unwrap();
+        // 同一计划时间已执行过，释放后也不会再执行
+        assert!(
+            !try_acquire_job_lock(&job, slot(0), "b:1", TTL)
+                .await
+                .unwrap()
+        );
+        assert!(
+            try_acquire_job_lock(&job, slot(1000), "b:1", TTL)
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URI"]
+    async fn test_job_lock_ttl_expiry() {
+        let job = job("ttl").await;
+
+        let ttl = Duration::from_millis(500);
+        assert!(
+            try_acquire_job_lock(&job, slot(0), "a:1", ttl)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !try_acquire_job_lock(&job, slot(1000), "b:1", TTL)
+                .await
+                .unwrap()
+        );
+
+        // 持有者崩溃未释放，过期后其他实例可以取得锁
+        tokio::time::sleep(Duration::from_millis(600)).await;
+        assert!(
+            try_acquire_job_lock(&job, slot(1000), "b:1", TTL)
+                .await
+                .unwrap()
+        );
+        // 原持有者无法再续期
+        assert!(!renew_job_lock(&job, "a:1", TTL).await.unwrap());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URI"]
+    async fn test_job_lock_renewal() {
+        let job = job("renew").await;
+
+        let ttl = Duration::from_millis(500);
+        assert!(
+            try_acquire_job_lock(&job, slot(0), "a:1", ttl)
+                .await
+                .unwrap()
+        );
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(renew_job_lock(&job, "a:1", TTL).await.unwrap());
+        // 续期后超过原有效期也仍被占用
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(
+            !try_acquire_job_lock(&job, slot(1000), "b:1", TTL)
+                .await
+                .unwrap()
+        );
+        assert!(!renew_job_lock(&job, "b:1", TTL).await.unwrap());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_run_with_heartbeat() {
+        let beats = Cell::new(0);
+        let task = async {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            "done"
+        };
+        let result = run_with_heartbeat(task, Duration::from_secs(3), || {
+            beats.set(beats.get() + 1);
+            async {}
+        })
+        .await;
+        assert_eq!(result, "done");
+        // 第 3、6、9 秒各续期一次
+        assert_eq!(beats.get(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_run_with_heartbeat_short_task() {
+        let beats = Cell::new(0);
+        let result = run_with_heartbeat(async { 1 }, Duration::from_secs(3), || {
+            beats.set(beats.get() + 1);
+            async {}
+        })
+        .await;
+        assert_eq!(result, 1);
+        assert_eq!(beats.get(), 0);
+    }
+}
@@ -0,0 +1,186 @@
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use futures_util::stream::TryStreamExt;
+    use maimap_utils::db::{
+        Database, DateTime, Document, doc, ensure_test_mongodb_connected, get_mongodb_client,
+        release_job_lock, try_acquire_job_lock,
+    };
+    use maimap_utils::errors::Result;
+    use maimap_utils::migrations::{Migration, Migrator, pending_versions, rollback_versions};
+    use std::collections::HashSet;
+    use std::time::Duration;
+
+    const VERSIONS: [i64; 3] = [1, 2, 3];
+
+    fn applied(versions: &[i64]) -> HashSet<i64> {
+        versions.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_pending_versions() {
+        assert_eq!(
+            pending_versions(&VERSIONS, &applied(&[]), None),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            pending_versions(&VERSIONS, &applied(&[1]), None),
+            vec![2, 3]
+        );
+        assert_eq!(
+            pending_versions(&VERSIONS, &applied(&[]), Some(2)),
+            vec![1, 2]
+        );
+        assert_eq!(
+            pending_versions(&[3, 1, 2], &applied(&[2]), None),
+            vec![1, 3]
+        );
+        assert!(pending_versions(&VERSIONS, &applied(&VERSIONS), None).is_empty());
+    }
+
+    #[test]
+    fn test_rollback_versions() {
+        // 不指定目标时只回滚最近的一个
+        assert_eq!(
+            rollback_versions(&VERSIONS, &applied(&[1, 2]), None),
+            vec![2]
+        );
+        assert_eq!(
+            rollback_versions(&VERSIONS, &applied(&VERSIONS), Some(1)),
+            vec![3, 2]
+        );
+        assert_eq!(
+            rollback_versions(&VERSIONS, &applied(&VERSIONS), Some(0)),
+            vec![3, 2, 1]
+        );
+        assert!(rollback_versions(&VERSIONS, &applied(&[1]), Some(1)).is_empty());
+        assert!(rollback_versions(&VERSIONS, &applied(&[]), None).is_empty());
+    }
+
+    /// 执行与回滚时在 `migration_log` 中记录版本号的迁移
+    struct Logged(i64);
+
+    #[async_trait]
+    impl Migration for Logged {
+        fn version(&self) -> i64 {
+            self.0
+        }
+
+        fn name(&self) -> &str {
+            "logged"
+        }
+
+        async fn up(&self, db: &Database) -> Result<()> {
+            let log = db.collection::<Document>("migration_log");
+            log.insert_one(doc! { "up": self.0 }).await?;
+            Ok(())
+        }
+
+        async fn down(&self, db: &Database) -> Result<()> {
+            let log = db.collection::<Document>("migration_log");
+            log.insert_one(doc! { "down": self.0 }).await?;
+            Ok(())
+        }
+    }
+
+    /// 需要 `TEST_DATABASE_URI` 指向可用的 MongoDB。
+    /// 每个测试使用单独的数据库，迁移锁按数据库区分，测试之间互不影响。
+    async fn migrator(name: &str) -> (Migrator, Database) {
+        ensure_test_mongodb_connected().await;
+        let db = get_mongodb_client().database(&format!("maimap_test_migrations_{}", name));
+        db.drop().await.unwrap();
+        let migrations: Vec<Box<dyn Migration>> = VERSIONS
+            .iter()
+            .map(|version| Box::new(Logged(*version)) as Box<dyn Migration>)
+            .collect();
+        (Migrator::new(db.clone(), migrations), db)
+    }
+
+    async fn log(db: &Database) -> Vec<Document> {
+        db.collection::<Document>("migration_log")
+            .find(doc! {})
+            .sort(doc! { "_id": 1 })
+            .projection(doc! { "_id": 0 })
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap()
+    }
+
+    async fn recorded(migrator: &Migrator) -> Vec<i64> {
+        migrator
+            .status()
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|status| status.applied_at.is_some())
+            .map(|status| status.version)
+            .collect()
+    }
+
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URI"]
+    async fn test_migrator_up_and_down() {
+        let (migrator, db) = migrator("up_down").await;
+
+        assert_eq!(migrator.up(Some(2), false).await.unwrap(), vec![1, 2]);
+        assert_eq!(recorded(&migrator).await, vec![1, 2]);
+        assert_eq!(migrator.up(None, false).await.unwrap(), vec![3]);
+        // 已执行过的迁移不会重复执行
+        assert!(migrator.up(None, false).await.unwrap().is_empty());
+
+        assert_eq!(migrator.down(None, false).await.unwrap(), vec![3]);
+        assert_eq!(migrator.down(Some(0), false).await.unwrap(), vec![2, 1]);
+        assert!(recorded(&migrator).await.is_empty());
+
+        assert_eq!(
+            log(&db).await,
+            vec![
+                doc! { "up": 1_i64 },
+                doc! { "up": 2_i64 },
+                doc! { "up": 3_i64 },
+                doc! { "down": 3_i64 },
+                doc! { "down": 2_i64 },
+                doc! { "down": 1_i64 },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URI"]
+    async fn test_migrator_dry_run() {
+        let (migrator, db) = migrator("dry_run").await;
+
+        assert_eq!(migrator.up(None, true).await.unwrap(), vec![1, 2, 3]);
+        assert!(recorded(&migrator).await.is_empty());
+        assert!(log(&db).await.is_empty());
+
+        migrator.up(None, false).await.unwrap();
+        assert_eq!(migrator.down(Some(1), true).await.unwrap(), vec![3, 2]);
+        assert_eq!(recorded(&migrator).await, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URI"]
+    async fn test_migrator_lock() {
+        let (migrator, db) = migrator("lock").await;
+
+        // 模拟另一个进程正在执行迁移
+        let lock = format!("migrations:{}", db.name());
+        let ttl = Duration::from_secs(60);
+        assert!(
+            try_acquire_job_lock(&lock, DateTime::now(), "other:1", ttl)
+                .await
+                .unwrap()
+        );
+        assert!(migrator.up(None, false).await.is_err());
+        assert!(migrator.down(None, false).await.is_err());
+        assert!(recorded(&migrator).await.is_empty());
+        // dry-run 不需要锁
+        assert_eq!(migrator.up(None, true).await.unwrap(), vec![1, 2, 3]);
+
+        release_job_lock(&lock, "other:1").await.unwrap();
+        assert_eq!(migrator.up(None, false).await.unwrap(), vec![1, 2, 3]);
+    }
+}
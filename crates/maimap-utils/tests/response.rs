@@ -0,0 +1,218 @@
+#[cfg(test)]
+mod tests {
+    use maimap_derive::ToResponse;
+    use maimap_utils::traits::ToResponse;
+    use maimap_utils::types::{ArcadeSearchResultResponse, JobRunStatus, JobStatus};
+    use mongodb::bson::oid::ObjectId;
+    use mongodb::bson::{DateTime, Decimal128};
+    use serde::Serialize;
+    use serde_json::{Value, json};
+
+    fn price(cost: &Option<f64>) -> Value {
+        json!(cost.map(|cost| format!("{:.1}元", cost)))
+    }
+
+    #[derive(Serialize, ToResponse)]
+    struct Location {
+        province: String,
+        #[response(rename = "lat")]
+        latitude: Decimal128,
+    }
+
+    #[derive(Serialize, ToResponse)]
+    struct Shop {
+        #[serde(rename = "_id")]
+        id: ObjectId,
+        #[response(rename = "shop_name")]
+        name: String,
+        #[response(skip_if = "Option::is_none")]
+        phone: Option<String>,
+        #[response(with = price)]
+        cost: Option<f64>,
+        #[response(flatten)]
+        location: Location,
+        #[DoNotRespond]
+        #[allow(dead_code)]
+        missing_count: i32,
+        created_at: DateTime,
+    }
+
+    fn shop(phone: Option<&str>) -> Shop {
+        Shop {
+            id: ObjectId::parse_str("67fcfa2b8c5b2f0001a1b2c3").unwrap(),
+            name: "大玩家".to_string(),
+            phone: phone.map(str::to_string),
+            cost: Some(2.0),
+            location: Location {
+                province: "上海".to_string(),
+                latitude: "31.2304".parse().unwrap(),
+            },
+            missing_count: 1,
+            created_at: DateTime::from_millis(1744632360000),
+        }
+    }
+
+    #[test]
+    fn test_response_options() {
+        assert_eq!(
+            shop(Some("021-12345678")).to_response(),
+            json!({
+                "id": "67fcfa2b8c5b2f0001a1b2c3",
+                "shop_name": "大玩家",
+                "phone": "021-12345678",
+                "cost": "2.0元",
+                "province": "上海",
+                "lat": 31.2304,
+                "created_at": "2025-04-14T12:06:00.000Z",
+            })
+        );
+    }
+
+    #[test]
+    fn test_response_skip_if() {
+        let response = shop(None).to_response();
+        assert!(response.get("phone").is_none());
+        assert!(response.get("missing_count").is_none());
+        assert_eq!(response["shop_name"], "大玩家");
+    }
+
+    #[derive(ToResponse)]
+    enum Change {
+        Opened,
+        #[response(rename = "closed")]
+        MarkedClosed,
+        Renamed(String),
+        Moved(Decimal128, Decimal128),
+        Merged {
+            into: ObjectId,
+            #[DoNotRespond]
+            #[allow(dead_code)]
+            reason: String,
+        },
+    }
+
+    #[derive(ToResponse)]
+    struct History {
+        user_ids: Vec<ObjectId>,
+        last_seen_at: Option<DateTime>,
+        rating: Option<Decimal128>,
+        removed_at: Option<DateTime>,
+        location: Option<Location>,
+        changes: Vec<Change>,
+    }
+
+    #[test]
+    fn test_response_nested_types() {
+        let history = History {
+            user_ids: vec![ObjectId::parse_str("67fcfa2b8c5b2f0001a1b2c3").unwrap()],
+            last_seen_at: Some(DateTime::from_millis(1744632360000)),
+            rating: Some("4.5".parse().unwrap()),
+            removed_at: None,
+            location: Some(Location {
+                province: "上海".to_string(),
+                latitude: "31.2304".parse().unwrap(),
+            }),
+            changes: vec![
+                Change::Opened,
+                Change::MarkedClosed,
+                Change::Renamed("大玩家超乐场".to_string()),
+                Change::Moved("31.2".parse().unwrap(), "121.4".parse().unwrap()),
+                Change::Merged {
+                    into: ObjectId::parse_str("67fcfa2b8c5b2f0001a1b2c4").unwrap(),
+                    reason: "重复".to_string(),
+                },
+            ],
+        };
+
+        assert_eq!(
+            history.to_response(),
+            json!({
+                "user_ids": ["67fcfa2b8c5b2f0001a1b2c3"],
+                "last_seen_at": "2025-04-14T12:06:00.000Z",
+                "rating": 4.5,
+                "removed_at": null,
+                "location": { "province": "上海", "lat": 31.2304 },
+                "changes": [
+                    "opened",
+                    "closed",
+                    { "renamed": "大玩家超乐场" },
+                    { "moved": [31.2, 121.4] },
+                    { "merged": { "into": "67fcfa2b8c5b2f0001a1b2c4" } },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_job_status_response() {
+        let status = JobStatus {
+            job: "scrape".to_string(),
+            schedule: "0 0 4 * * *".to_string(),
+            next_run_at: Some(DateTime::from_millis(1744632360000)),
+            last_started_at: None,
+            last_finished_at: None,
+            last_status: Some(JobRunStatus::Succeeded),
+            last_error: None,
+            last_owner: None,
+        };
+        assert_eq!(
+            status.to_response(),
+            json!({
+                "job": "scrape",
+                "schedule": "0 0 4 * * *",
+                "next_run_at": "2025-04-14T12:06:00.000Z",
+                "last_started_at": null,
+                "last_finished_at": null,
+                "last_status": "succeeded",
+                "last_error": null,
+                "last_owner": null,
+            })
+        );
+    }
+
+    #[test]
+    fn test_typed_response_round_trip() {
+        for shop in [shop(Some("021-12345678")), shop(None)] {
+            let typed = shop.to_typed_response();
+            assert_eq!(serde_json::to_value(&typed).unwrap(), shop.to_response());
+            let parsed: ShopResponse = serde_json::from_value(shop.to_response()).unwrap();
+            assert_eq!(parsed, typed);
+        }
+
+        let response = shop(None).to_typed_response();
+        assert_eq!(response.name, "大玩家");
+        assert_eq!(response.phone, None);
+        assert_eq!(response.location.latitude, 31.2304);
+        assert_eq!(response.created_at, "2025-04-14T12:06:00.000Z");
+    }
+
+    #[test]
+    fn test_arcade_search_result_response() {
+        let value = json!({
+            "arcade_address": "上海市黄浦区",
+            "arcade_cost": 2.0,
+            "arcade_count": 4,
+            "arcade_dead": false,
+            "arcade_id": 1514,
+            "arcade_lat": 31.2304,
+            "arcade_lng": 121.4737,
+            "arcade_name": "大玩家",
+            "arcade_store_id": null,
+            "arcade_province": "上海",
+            "arcade_machine": null,
+            "created_at": "2025-04-14T12:06:00.000Z",
+            "distance": 120.5,
+        });
+        let parsed: ArcadeSearchResultResponse = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(parsed.arcade.arcade_id, 1514);
+        assert_eq!(parsed.distance, Some(120.5));
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), value);
+
+        // 非地理位置搜索时不输出 distance
+        let mut value = value;
+        value.as_object_mut().unwrap().remove("distance");
+        let parsed: ArcadeSearchResultResponse = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(parsed.distance, None);
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), value);
+    }
+}